@@ -5,3 +5,12 @@ pub const ADMIN_STATE_SEED: &[u8] = b"admin_state";
 
 /// Maximum fee rate (1%)
 pub const MAX_FEE_RATE: u16 = 100;
+
+/// Market maker registry entry seed
+pub const MARKET_MAKER_SEED: &[u8] = b"market_maker";
+
+/// Swap fee tier schedule seed
+pub const FEE_SCHEDULE_SEED: &[u8] = b"fee_schedule";
+
+/// Maximum number of volume fee tiers
+pub const MAX_FEE_TIERS: usize = 8;
@@ -16,4 +16,7 @@ pub enum AdminError {
     
     #[msg("Cannot withdraw while trading is active")]
     TradingActive,
+    
+    #[msg("Invalid fee tier schedule")]
+    InvalidFeeSchedule,
 }
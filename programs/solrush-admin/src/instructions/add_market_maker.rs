@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::{constants::*, errors::AdminError, state::{AdminState, MarketMaker}};
+
+#[derive(Accounts)]
+#[instruction(maker: Pubkey)]
+pub struct AddMarketMaker<'info> {
+    #[account(
+        mut,
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        constraint = admin_state.admin == admin.key() @ AdminError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MarketMaker::LEN,
+        seeds = [MARKET_MAKER_SEED, maker.as_ref()],
+        bump
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddMarketMaker>, maker: Pubkey) -> Result<()> {
+    let market_maker = &mut ctx.accounts.market_maker;
+    let clock = Clock::get()?;
+
+    market_maker.bump = ctx.bumps.market_maker;
+    market_maker.maker = maker;
+    market_maker.added_at = clock.unix_timestamp;
+    ctx.accounts.admin_state.last_action_time = clock.unix_timestamp;
+
+    emit!(MarketMakerAdded {
+        maker,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketMakerAdded {
+    pub maker: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::{constants::*, errors::AdminError, state::{AdminState, FeeSchedule, FeeTier}};
+
+#[derive(Accounts)]
+pub struct InitializeFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        constraint = admin_state.admin == admin.key() @ AdminError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = FeeSchedule::LEN,
+        seeds = [FEE_SCHEDULE_SEED],
+        bump
+    )]
+    pub fee_schedule: Account<'info, FeeSchedule>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeFeeSchedule>,
+    tiers: Vec<FeeTier>,
+    staking_discount_bps: u16,
+    min_staked_amount: u64,
+    maker_rebate_bps: u16,
+) -> Result<()> {
+    FeeSchedule::validate(&tiers, staking_discount_bps, maker_rebate_bps)?;
+
+    let fee_schedule = &mut ctx.accounts.fee_schedule;
+    let clock = Clock::get()?;
+
+    fee_schedule.bump = ctx.bumps.fee_schedule;
+    fee_schedule.tiers = tiers;
+    fee_schedule.staking_discount_bps = staking_discount_bps;
+    fee_schedule.min_staked_amount = min_staked_amount;
+    fee_schedule.maker_rebate_bps = maker_rebate_bps;
+    fee_schedule.last_updated = clock.unix_timestamp;
+    ctx.accounts.admin_state.last_action_time = clock.unix_timestamp;
+
+    emit!(FeeScheduleUpdated {
+        tier_count: fee_schedule.tiers.len() as u8,
+        staking_discount_bps,
+        min_staked_amount,
+        maker_rebate_bps,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct FeeScheduleUpdated {
+    pub tier_count: u8,
+    pub staking_discount_bps: u16,
+    pub min_staked_amount: u64,
+    pub maker_rebate_bps: u16,
+    pub timestamp: i64,
+}
@@ -4,6 +4,10 @@ pub mod resume_trading;
 pub mod update_fee_rate;
 pub mod transfer_admin;
 pub mod emergency_withdraw;
+pub mod add_market_maker;
+pub mod remove_market_maker;
+pub mod initialize_fee_schedule;
+pub mod update_fee_schedule;
 
 pub use initialize_admin::*;
 pub use pause_trading::*;
@@ -11,3 +15,7 @@ pub use resume_trading::*;
 pub use update_fee_rate::*;
 pub use transfer_admin::*;
 pub use emergency_withdraw::*;
+pub use add_market_maker::*;
+pub use remove_market_maker::*;
+pub use initialize_fee_schedule::*;
+pub use update_fee_schedule::*;
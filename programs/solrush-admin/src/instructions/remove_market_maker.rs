@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::{constants::*, errors::AdminError, state::{AdminState, MarketMaker}};
+
+#[derive(Accounts)]
+pub struct RemoveMarketMaker<'info> {
+    #[account(
+        mut,
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        constraint = admin_state.admin == admin.key() @ AdminError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_MAKER_SEED, market_maker.maker.as_ref()],
+        bump = market_maker.bump,
+        close = admin
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveMarketMaker>) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.admin_state.last_action_time = clock.unix_timestamp;
+
+    emit!(MarketMakerRemoved {
+        maker: ctx.accounts.market_maker.maker,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketMakerRemoved {
+    pub maker: Pubkey,
+    pub timestamp: i64,
+}
@@ -45,4 +45,48 @@ pub mod solrush_admin {
     pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
         instructions::emergency_withdraw::handler(ctx)
     }
+
+    /// Whitelist a market maker for RFQ settlement
+    pub fn add_market_maker(ctx: Context<AddMarketMaker>, maker: Pubkey) -> Result<()> {
+        instructions::add_market_maker::handler(ctx, maker)
+    }
+
+    /// Remove a market maker from the RFQ whitelist
+    pub fn remove_market_maker(ctx: Context<RemoveMarketMaker>) -> Result<()> {
+        instructions::remove_market_maker::handler(ctx)
+    }
+
+    /// Create the swap fee tier schedule
+    pub fn initialize_fee_schedule(
+        ctx: Context<InitializeFeeSchedule>,
+        tiers: Vec<FeeTier>,
+        staking_discount_bps: u16,
+        min_staked_amount: u64,
+        maker_rebate_bps: u16,
+    ) -> Result<()> {
+        instructions::initialize_fee_schedule::handler(
+            ctx,
+            tiers,
+            staking_discount_bps,
+            min_staked_amount,
+            maker_rebate_bps,
+        )
+    }
+
+    /// Replace the swap fee tier schedule
+    pub fn update_fee_schedule(
+        ctx: Context<UpdateFeeSchedule>,
+        tiers: Vec<FeeTier>,
+        staking_discount_bps: u16,
+        min_staked_amount: u64,
+        maker_rebate_bps: u16,
+    ) -> Result<()> {
+        instructions::update_fee_schedule::handler(
+            ctx,
+            tiers,
+            staking_discount_bps,
+            min_staked_amount,
+            maker_rebate_bps,
+        )
+    }
 }
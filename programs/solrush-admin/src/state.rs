@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::{constants::MAX_FEE_TIERS, errors::AdminError};
 
 /// Admin state
 #[account]
@@ -14,3 +15,61 @@ pub struct AdminState {
 impl AdminState {
     pub const LEN: usize = 8 + 1 + 32 + 1 + (4 + 200) + 8 + 8;
 }
+
+/// Whitelisted RFQ market maker; the account existing is the whitelist entry
+#[account]
+pub struct MarketMaker {
+    pub bump: u8,
+    pub maker: Pubkey,
+    pub added_at: i64,
+}
+
+impl MarketMaker {
+    pub const LEN: usize = 8 + 1 + 32 + 8;
+}
+
+/// Fee discount for traders above a rolling 30-day volume
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_volume: u64,
+    pub discount_bps: u16,
+}
+
+/// Swap fee tier schedule; discounts are basis points of the pool fee
+#[account]
+pub struct FeeSchedule {
+    pub bump: u8,
+    pub tiers: Vec<FeeTier>,
+    pub staking_discount_bps: u16,
+    pub min_staked_amount: u64,
+    pub maker_rebate_bps: u16,
+    pub last_updated: i64,
+}
+
+impl FeeSchedule {
+    pub const LEN: usize = 8 + 1 + (4 + MAX_FEE_TIERS * (8 + 2)) + 2 + 8 + 2 + 8;
+
+    /// Tiers must ascend in volume and discount, and no discount may exceed the fee
+    pub fn validate(
+        tiers: &[FeeTier],
+        staking_discount_bps: u16,
+        maker_rebate_bps: u16,
+    ) -> Result<()> {
+        require!(tiers.len() <= MAX_FEE_TIERS, AdminError::InvalidFeeSchedule);
+        require!(
+            staking_discount_bps <= 10000 && maker_rebate_bps <= 10000,
+            AdminError::InvalidFeeSchedule
+        );
+        require!(
+            tiers.iter().all(|tier| tier.discount_bps <= 10000),
+            AdminError::InvalidFeeSchedule
+        );
+        require!(
+            tiers.windows(2).all(|pair| {
+                pair[0].min_volume < pair[1].min_volume && pair[0].discount_bps <= pair[1].discount_bps
+            }),
+            AdminError::InvalidFeeSchedule
+        );
+        Ok(())
+    }
+}
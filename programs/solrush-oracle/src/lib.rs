@@ -0,0 +1,151 @@
+//! Pyth v2 price account layout shared by the swap and perpetual programs.
+//!
+//! Each program maps `PythError` onto its own error enum and applies its own
+//! price scaling and confidence limits on top of the raw aggregate read here.
+
+/// Pyth price account magic number
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Pyth account type for price accounts
+pub const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+
+/// Pyth aggregate status for a price that is currently trading
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Seconds a publish time may run ahead of the validator clock
+pub const MAX_CLOCK_SKEW: i64 = 5;
+
+// Byte offsets in a Pyth v2 price account
+pub const PYTH_MAGIC_OFFSET: usize = 0;
+pub const PYTH_ACCOUNT_TYPE_OFFSET: usize = 8;
+pub const PYTH_EXPONENT_OFFSET: usize = 20;
+pub const PYTH_TIMESTAMP_OFFSET: usize = 96;
+pub const PYTH_AGG_PRICE_OFFSET: usize = 208;
+pub const PYTH_AGG_CONF_OFFSET: usize = 216;
+pub const PYTH_AGG_STATUS_OFFSET: usize = 224;
+pub const PYTH_ACCOUNT_MIN_LEN: usize = 240;
+
+/// Reasons a price account or publish time is rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PythError {
+    /// Data is too short or is not a Pyth price account
+    InvalidAccount,
+    /// Published more than the allowed staleness ago
+    Stale,
+    /// Published further in the future than `MAX_CLOCK_SKEW`
+    FuturePublishTime,
+}
+
+/// Aggregate price read from a Pyth v2 price account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub status: u32,
+}
+
+impl PythPrice {
+    pub fn is_trading(&self) -> bool {
+        self.status == PYTH_STATUS_TRADING
+    }
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], PythError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(PythError::InvalidAccount)
+}
+
+/// Parse the aggregate price from Pyth v2 price account data
+///
+/// The aggregate status is returned as is; callers decide whether a price
+/// that is not trading is usable.
+pub fn parse_pyth_price(data: &[u8]) -> Result<PythPrice, PythError> {
+    if data.len() < PYTH_ACCOUNT_MIN_LEN
+        || u32::from_le_bytes(read_bytes(data, PYTH_MAGIC_OFFSET)?) != PYTH_MAGIC
+        || u32::from_le_bytes(read_bytes(data, PYTH_ACCOUNT_TYPE_OFFSET)?) != PYTH_ACCOUNT_TYPE_PRICE
+    {
+        return Err(PythError::InvalidAccount);
+    }
+
+    Ok(PythPrice {
+        price: i64::from_le_bytes(read_bytes(data, PYTH_AGG_PRICE_OFFSET)?),
+        conf: u64::from_le_bytes(read_bytes(data, PYTH_AGG_CONF_OFFSET)?),
+        expo: i32::from_le_bytes(read_bytes(data, PYTH_EXPONENT_OFFSET)?),
+        publish_time: i64::from_le_bytes(read_bytes(data, PYTH_TIMESTAMP_OFFSET)?),
+        status: u32::from_le_bytes(read_bytes(data, PYTH_AGG_STATUS_OFFSET)?),
+    })
+}
+
+/// Accept a publish time no older than `max_staleness` seconds and no more
+/// than `MAX_CLOCK_SKEW` seconds ahead of `now`
+pub fn check_publish_time(publish_time: i64, now: i64, max_staleness: i64) -> Result<(), PythError> {
+    if publish_time > now.saturating_add(MAX_CLOCK_SKEW) {
+        return Err(PythError::FuturePublishTime);
+    }
+    if now.saturating_sub(publish_time) > max_staleness {
+        return Err(PythError::Stale);
+    }
+
+    Ok(())
+}
+
+/// Pyth v2 price account data with a trading aggregate, for tests
+#[cfg(any(test, feature = "test-utils"))]
+pub fn mock_pyth_account(price: i64, conf: u64, expo: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = vec![0u8; PYTH_ACCOUNT_MIN_LEN];
+    data[PYTH_MAGIC_OFFSET..PYTH_MAGIC_OFFSET + 4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[PYTH_ACCOUNT_TYPE_OFFSET..PYTH_ACCOUNT_TYPE_OFFSET + 4]
+        .copy_from_slice(&PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes());
+    data[PYTH_EXPONENT_OFFSET..PYTH_EXPONENT_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+    data[PYTH_TIMESTAMP_OFFSET..PYTH_TIMESTAMP_OFFSET + 8].copy_from_slice(&publish_time.to_le_bytes());
+    data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+    data[PYTH_AGG_CONF_OFFSET..PYTH_AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+    data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4]
+        .copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pyth_price() {
+        let data = mock_pyth_account(150_00000000, 5_000000, -8, 1_000);
+        let price = parse_pyth_price(&data).unwrap();
+        assert_eq!(price.price, 150_00000000);
+        assert_eq!(price.conf, 5_000000);
+        assert_eq!(price.expo, -8);
+        assert_eq!(price.publish_time, 1_000);
+        assert!(price.is_trading());
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = 0;
+        assert_eq!(parse_pyth_price(&bad_magic), Err(PythError::InvalidAccount));
+        assert_eq!(parse_pyth_price(&data[..100]), Err(PythError::InvalidAccount));
+
+        let mut halted = data.clone();
+        halted[PYTH_AGG_STATUS_OFFSET] = 0;
+        assert!(!parse_pyth_price(&halted).unwrap().is_trading());
+    }
+
+    #[test]
+    fn test_check_publish_time() {
+        assert!(check_publish_time(1_000, 1_060, 60).is_ok());
+        assert_eq!(check_publish_time(1_000, 1_061, 60), Err(PythError::Stale));
+
+        // Small skew ahead of the clock is tolerated, more is not
+        assert!(check_publish_time(1_000 + MAX_CLOCK_SKEW, 1_000, 60).is_ok());
+        assert_eq!(
+            check_publish_time(1_001 + MAX_CLOCK_SKEW, 1_000, 60),
+            Err(PythError::FuturePublishTime)
+        );
+        assert_eq!(
+            check_publish_time(i64::MAX, 1_000, 60),
+            Err(PythError::FuturePublishTime)
+        );
+    }
+}
@@ -1,22 +1,58 @@
 use anchor_lang::prelude::*;
 
-/// Minimum leverage
-pub const MIN_LEVERAGE: u8 = 2;
+/// Lowest leverage a market can allow
+pub const MIN_LEVERAGE: u8 = 1;
 
-/// Maximum leverage
-pub const MAX_LEVERAGE: u8 = 10;
+/// Highest leverage a market can allow
+pub const MAX_LEVERAGE: u8 = 50;
 
-/// Maintenance margin rate (10%)
-pub const MAINTENANCE_MARGIN_RATE: u64 = 1000; // basis points
+/// Highest trading fee a market can charge (1%)
+pub const MAX_TRADING_FEE: u16 = 100; // basis points
 
-/// Liquidation fee (2%)
-pub const LIQUIDATION_FEE: u64 = 200; // basis points
+/// Highest liquidation fee a market can charge (10%)
+pub const MAX_LIQUIDATION_FEE: u16 = 1000; // basis points
+
+/// Share of trading fees paid into the insurance fund (50%)
+pub const INSURANCE_FEE_SHARE: u64 = 5000; // basis points
+
+/// Share of liquidation fees paid into the insurance fund (50%)
+pub const INSURANCE_LIQUIDATION_SHARE: u64 = 5000; // basis points
+
+/// Fee paid to the keeper executing a take-profit or stop-loss (0.05%)
+pub const KEEPER_FEE: u64 = 5; // basis points of the closed size
+
+/// Margin above maintenance a partial liquidation restores (2%)
+pub const PARTIAL_LIQUIDATION_BUFFER: u64 = 200; // basis points
+
+/// Smallest position size liquidated partially instead of in full
+pub const PARTIAL_LIQUIDATION_MIN_SIZE: u64 = 10_000_000_000; // 10,000 units of a 6-decimal collateral
+
+/// Cumulative funding is a fraction of notional scaled by 1e9
+pub const FUNDING_PRECISION: i128 = 1_000_000_000;
+
+/// Minimum time between funding payments
+pub const FUNDING_INTERVAL: i64 = 3600; // 1 hour
+
+/// Period over which the full premium is paid as funding
+pub const FUNDING_PERIOD: i64 = 86400; // 1 day
+
+/// Mark price premium of a fully one-sided market (1%)
+pub const MAX_MARK_PREMIUM: u64 = 100; // basis points
 
 /// Position state seed
 pub const POSITION_SEED: &[u8] = b"position";
 
+/// Trader position counter seed
+pub const TRADER_ACCOUNT_SEED: &[u8] = b"trader_account";
+
 /// Funding rate seed
 pub const FUNDING_RATE_SEED: &[u8] = b"funding_rate";
 
 /// Collateral vault seed
 pub const COLLATERAL_VAULT_SEED: &[u8] = b"collateral_vault";
+
+/// Insurance fund vault seed
+pub const INSURANCE_FUND_SEED: &[u8] = b"insurance_fund";
+
+/// Perp market config seed
+pub const PERP_MARKET_SEED: &[u8] = b"perp_market";
@@ -31,4 +31,48 @@ pub enum PerpetualError {
     
     #[msg("Liquidation threshold not reached")]
     LiquidationThresholdNotReached,
+    
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+    
+    #[msg("Invalid collateral account")]
+    InvalidCollateralAccount,
+    
+    #[msg("Collateral vault cannot cover the payout")]
+    InsufficientVaultBalance,
+    
+    #[msg("Market has no bad debt to deleverage")]
+    NoBadDebt,
+    
+    #[msg("No open positions to auto-deleverage")]
+    InvalidAdlTarget,
+
+    
+    #[msg("Invalid market parameters")]
+    InvalidMarketParams,
+    
+    #[msg("Market is paused")]
+    MarketPaused,
+    
+    #[msg("Open interest limit exceeded")]
+    OpenInterestLimitExceeded,
+    
+    #[msg("Invalid take-profit or stop-loss price")]
+    InvalidTriggerPrice,
+    
+    #[msg("Take-profit or stop-loss price not reached")]
+    TriggerNotReached,
+    
+    #[msg("Oracle publish time is ahead of the clock")]
+    FutureOraclePrice,
+
 }
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::{errors::PerpetualError, state::Position};
+use crate::{constants::*, errors::PerpetualError, state::{FundingRate, PerpMarket, Position}, utils::*};
 
 #[derive(Accounts)]
 pub struct AddMargin<'info> {
@@ -11,10 +11,29 @@ pub struct AddMargin<'info> {
     )]
     pub position: Account<'info, Position>,
 
-    #[account(mut)]
+    #[account(
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    #[account(
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
     pub collateral_vault: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+    )]
     pub user_collateral_account: Account<'info, TokenAccount>,
 
     pub user: Signer<'info>,
@@ -36,15 +55,38 @@ pub fn handler(ctx: Context<AddMargin>, additional_collateral: u64) -> Result<()
         additional_collateral,
     )?;
 
+    // Settle funding accrued so far against the topped-up collateral
     let position = &mut ctx.accounts.position;
-    position.collateral = position.collateral
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let collateral = position.collateral
         .checked_add(additional_collateral)
         .ok_or(PerpetualError::Overflow)?;
+    let (collateral, shortfall) = apply_funding(collateral, funding_payment)?;
+    require!(shortfall == 0, PerpetualError::Undercollateralized);
+
+    position.collateral = collateral;
+    position.funding_index = funding_index;
+    position.liquidation_price = calculate_liquidation_price(
+        position.entry_price,
+        position.size,
+        position.side,
+        collateral,
+        0,
+        &ctx.accounts.market.params,
+    )?;
 
     emit!(MarginAdded {
         position: position.key(),
         amount: additional_collateral,
+        funding_payment,
         new_collateral: position.collateral,
+        liquidation_price: position.liquidation_price,
         timestamp: Clock::get()?.unix_timestamp,
     });
 
@@ -55,6 +97,8 @@ pub fn handler(ctx: Context<AddMargin>, additional_collateral: u64) -> Result<()
 pub struct MarginAdded {
     pub position: Pubkey,
     pub amount: u64,
+    pub funding_payment: i64,
     pub new_collateral: u64,
+    pub liquidation_price: u64,
     pub timestamp: i64,
 }
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::*, state::{FundingRate, PerpMarket, PositionSide}, utils::*};
+
+/// Charge uncovered bad debt to the open positions opposite it (permissionless)
+///
+/// The loss is booked on the side's socialized loss index, so no position
+/// accounts are needed however many positions are open.
+#[derive(Accounts)]
+pub struct AutoDeleverage<'info> {
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+        constraint = market.total_bad_debt() > 0 @ PerpetualError::NoBadDebt,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, market.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<AutoDeleverage>, side: PositionSide) -> Result<()> {
+    let clock = Clock::get()?;
+    let market = &mut ctx.accounts.market;
+
+    // `side` repays the debt left by bankrupt positions on the other side
+    let bankrupt_side = side.opposite();
+    let (bad_debt, bankruptcy_price) = market.bad_debt(bankrupt_side);
+    require!(bad_debt > 0, PerpetualError::NoBadDebt);
+
+    // The insurance fund pays first, e.g. after it was refilled by fees
+    let covered = bad_debt.min(ctx.accounts.insurance_fund.amount);
+    transfer_from_market(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.insurance_fund.to_account_info(),
+        &ctx.accounts.collateral_vault.to_account_info(),
+        market,
+        covered,
+    )?;
+    market.repay_bad_debt(bankrupt_side, covered);
+
+    // The rest is paid by every open `side` position pro rata of its size,
+    // out of its collateral at its next settlement. Without open positions it
+    // stays on the market until the fund is refilled.
+    let open_interest = market.open_interest(side);
+    let (socialized, loss_per_unit) = if bad_debt > covered && open_interest > 0 {
+        let socialized = bad_debt - covered;
+        let loss_per_unit = socialize_loss(&mut ctx.accounts.funding_rate, side, socialized, open_interest)?;
+        market.repay_bad_debt(bankrupt_side, socialized);
+        (socialized, loss_per_unit)
+    } else {
+        (0, 0)
+    };
+
+    emit!(AutoDeleveraged {
+        market: market.key(),
+        keeper: ctx.accounts.keeper.key(),
+        side,
+        bankruptcy_price,
+        insurance_covered: covered,
+        socialized_loss: socialized,
+        loss_per_unit,
+        open_interest,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Bad debt of {}: {} covered by the insurance fund, {} socialized", bad_debt, covered, socialized);
+
+    Ok(())
+}
+
+#[event]
+pub struct AutoDeleveraged {
+    pub market: Pubkey,
+    pub keeper: Pubkey,
+    pub side: PositionSide,
+    pub bankruptcy_price: u64,
+    pub insurance_covered: u64,
+    pub socialized_loss: u64,
+    pub loss_per_unit: i64,
+    pub open_interest: u64,
+    pub timestamp: i64,
+}
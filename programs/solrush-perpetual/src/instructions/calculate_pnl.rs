@@ -1,16 +1,35 @@
 use anchor_lang::prelude::*;
-use crate::{state::{Position, PnLInfo}, utils::*};
+use crate::{constants::*, errors::PerpetualError, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position, PnLInfo}, utils::*};
 
 #[derive(Accounts)]
 pub struct CalculatePnL<'info> {
     pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
 }
 
 pub fn handler(ctx: Context<CalculatePnL>) -> Result<PnLInfo> {
     let position = &ctx.accounts.position;
 
-    // Mock current price (use oracle in production)
-    let current_price = 10500u64;
+    let current_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        Clock::get()?.unix_timestamp,
+    )?;
 
     let unrealized_pnl = calculate_unrealized_pnl(
         position.entry_price,
@@ -19,17 +38,30 @@ pub fn handler(ctx: Context<CalculatePnL>) -> Result<PnLInfo> {
         position.side,
     )?;
 
-    let is_liquidatable = should_liquidate(
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        ctx.accounts.funding_rate.funding_index(position.side),
+    )?;
+
+    let health = calculate_position_health(
+        position.entry_price,
         current_price,
-        position.liquidation_price,
+        position.size,
         position.side,
-    );
+        position.collateral,
+        funding_payment,
+        &ctx.accounts.market.params,
+    )?;
 
     Ok(PnLInfo {
         unrealized_pnl,
-        realized_pnl: 0,
-        funding_payment: 0,
-        liquidation_price: position.liquidation_price,
-        is_liquidatable,
+        realized_pnl: position.realized_pnl,
+        funding_payment,
+        equity: health.equity,
+        margin_ratio: health.margin_ratio,
+        liquidation_price: health.liquidation_price,
+        is_liquidatable: health.is_liquidatable,
     })
 }
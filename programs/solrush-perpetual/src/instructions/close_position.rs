@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::{errors::PerpetualError, state::Position};
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::*, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position}, utils::*};
 
 #[derive(Accounts)]
 pub struct ClosePosition<'info> {
@@ -11,42 +11,139 @@ pub struct ClosePosition<'info> {
     )]
     pub position: Account<'info, Position>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
     pub collateral_vault: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub user_collateral_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
 
-    /// CHECK: Vault authority PDA
-    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+        constraint = user_collateral_account.owner == user.key() @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub user_collateral_account: Account<'info, TokenAccount>,
 
     pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let exit_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
     let position = &mut ctx.accounts.position;
+    let market = &mut ctx.accounts.market;
+    market.remove_open_interest(position.side, position.size);
+    refresh_funding(&mut ctx.accounts.funding_rate, market, exit_price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
 
-    // Return collateral to user
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.collateral_vault.to_account_info(),
-                to: ctx.accounts.user_collateral_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-            },
-        ),
-        position.collateral,
+    let pnl = calculate_unrealized_pnl(
+        position.entry_price,
+        exit_price,
+        position.size,
+        position.side,
     )?;
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let fee = calculate_fee(position.size, market.params.trading_fee as u64)?;
+    let settlement = calculate_settlement(position.collateral, pnl, funding_payment, fee)?;
+
+    // Profits are paid out of the shared vault, i.e. other traders' collateral
+    require!(
+        ctx.accounts.collateral_vault.amount >= settlement.payout,
+        PerpetualError::InsufficientVaultBalance
+    );
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let collateral_vault = ctx.accounts.collateral_vault.to_account_info();
+    let insurance_fund = ctx.accounts.insurance_fund.to_account_info();
+
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.user_collateral_account.to_account_info(),
+        market,
+        settlement.payout,
+    )?;
+
+    // Part of the fee feeds the insurance fund, the rest stays in the vault
+    let insurance_share = calculate_fee(settlement.fee_paid, INSURANCE_FEE_SHARE)?;
+    transfer_from_market(&token_program, &collateral_vault, &insurance_fund, market, insurance_share)?;
+    market.total_fees = market.total_fees
+        .checked_add(settlement.fee_paid - insurance_share)
+        .ok_or(PerpetualError::Overflow)?;
+
+    if settlement.bad_debt > 0 {
+        let (net_collateral, _) = apply_funding(position.collateral, funding_payment)?;
+        let bankruptcy_price = calculate_bankruptcy_price(
+            position.entry_price,
+            position.size,
+            net_collateral,
+            position.side,
+        )?;
+        absorb_bad_debt(
+            &token_program,
+            &insurance_fund,
+            ctx.accounts.insurance_fund.amount,
+            &collateral_vault,
+            market,
+            settlement.bad_debt,
+            position.side,
+            bankruptcy_price,
+        )?;
+    }
 
+    position.funding_index = funding_index;
+    position.realized_pnl = position.realized_pnl
+        .checked_add(settlement.realized_pnl)
+        .ok_or(PerpetualError::Overflow)?;
     position.is_open = false;
-    position.closed_at = Some(Clock::get()?.unix_timestamp);
+    position.closed_at = Some(clock.unix_timestamp);
 
     emit!(PositionClosed {
         position: position.key(),
         owner: position.owner,
-        timestamp: Clock::get()?.unix_timestamp,
+        exit_price,
+        realized_pnl: settlement.realized_pnl,
+        funding_payment,
+        fee: settlement.fee_paid,
+        payout: settlement.payout,
+        bad_debt: settlement.bad_debt,
+        timestamp: clock.unix_timestamp,
     });
 
     Ok(())
@@ -56,5 +153,11 @@ pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
 pub struct PositionClosed {
     pub position: Pubkey,
     pub owner: Pubkey,
+    pub exit_price: u64,
+    pub realized_pnl: i64,
+    pub funding_payment: i64,
+    pub fee: u64,
+    pub payout: u64,
+    pub bad_debt: u64,
     pub timestamp: i64,
 }
@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::*, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position}, utils::*};
+
+#[derive(Accounts)]
+pub struct DecreasePosition<'info> {
+    #[account(
+        mut,
+        constraint = position.owner == user.key() @ PerpetualError::Unauthorized,
+        constraint = position.is_open @ PerpetualError::PositionClosed,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+        constraint = user_collateral_account.owner == user.key() @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub user_collateral_account: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Close part of a position; closing all of it goes through `close_position`
+pub fn handler(ctx: Context<DecreasePosition>, closed_size: u64) -> Result<()> {
+    require!(
+        closed_size > 0 && closed_size < ctx.accounts.position.size,
+        PerpetualError::InvalidPositionSize
+    );
+
+    let clock = Clock::get()?;
+    let exit_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
+    let position = &mut ctx.accounts.position;
+    let market = &mut ctx.accounts.market;
+    market.remove_open_interest(position.side, closed_size);
+    refresh_funding(&mut ctx.accounts.funding_rate, market, exit_price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+
+    // Funding is settled on the full size before it is split
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let (collateral, shortfall) = apply_funding(position.collateral, funding_payment)?;
+    require!(shortfall == 0, PerpetualError::Undercollateralized);
+
+    let pnl = calculate_unrealized_pnl(
+        position.entry_price,
+        exit_price,
+        position.size,
+        position.side,
+    )?;
+    let (released_collateral, settlement) = calculate_partial_close(
+        position.size,
+        collateral,
+        pnl,
+        closed_size,
+        &market.params,
+    )?;
+
+    // A position that cannot cover its own loss is left to liquidation
+    require!(settlement.bad_debt == 0, PerpetualError::Undercollateralized);
+    require!(
+        ctx.accounts.collateral_vault.amount >= settlement.payout,
+        PerpetualError::InsufficientVaultBalance
+    );
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let collateral_vault = ctx.accounts.collateral_vault.to_account_info();
+
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.user_collateral_account.to_account_info(),
+        market,
+        settlement.payout,
+    )?;
+
+    let insurance_share = calculate_fee(settlement.fee_paid, INSURANCE_FEE_SHARE)?;
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.insurance_fund.to_account_info(),
+        market,
+        insurance_share,
+    )?;
+    market.total_fees = market.total_fees
+        .checked_add(settlement.fee_paid - insurance_share)
+        .ok_or(PerpetualError::Overflow)?;
+
+    position.size -= closed_size;
+    position.collateral = collateral - released_collateral;
+    position.funding_index = funding_index;
+    position.realized_pnl = position.realized_pnl
+        .checked_add(settlement.realized_pnl)
+        .ok_or(PerpetualError::Overflow)?;
+    position.liquidation_price = calculate_liquidation_price(
+        position.entry_price,
+        position.size,
+        position.side,
+        position.collateral,
+        0,
+        &market.params,
+    )?;
+
+    emit!(PositionDecreased {
+        position: position.key(),
+        owner: position.owner,
+        exit_price,
+        closed_size,
+        remaining_size: position.size,
+        realized_pnl: settlement.realized_pnl,
+        funding_payment,
+        fee: settlement.fee_paid,
+        payout: settlement.payout,
+        liquidation_price: position.liquidation_price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PositionDecreased {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub exit_price: u64,
+    pub closed_size: u64,
+    pub remaining_size: u64,
+    pub realized_pnl: i64,
+    pub funding_payment: i64,
+    pub fee: u64,
+    pub payout: u64,
+    pub liquidation_price: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,213 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::*, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position, TriggerType}, utils::*};
+
+#[derive(Accounts)]
+pub struct ExecuteTriggerOrder<'info> {
+    #[account(
+        mut,
+        constraint = position.is_open @ PerpetualError::PositionClosed,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = keeper_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub keeper_collateral_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+        constraint = owner_collateral_account.owner == position.owner @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub owner_collateral_account: Account<'info, TokenAccount>,
+
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<ExecuteTriggerOrder>) -> Result<()> {
+    let clock = Clock::get()?;
+    let exit_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
+    let position = &mut ctx.accounts.position;
+    let trigger = check_trigger(position.side, exit_price, position.take_profit, position.stop_loss)
+        .ok_or(PerpetualError::TriggerNotReached)?;
+
+    let closed_size = if position.trigger_size == 0 {
+        position.size
+    } else {
+        position.trigger_size.min(position.size)
+    };
+    let is_partial = closed_size < position.size;
+
+    let market = &mut ctx.accounts.market;
+    market.remove_open_interest(position.side, closed_size);
+    refresh_funding(&mut ctx.accounts.funding_rate, market, exit_price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let (collateral, shortfall) = apply_funding(position.collateral, funding_payment)?;
+    require!(shortfall == 0, PerpetualError::Undercollateralized);
+
+    let pnl = calculate_unrealized_pnl(
+        position.entry_price,
+        exit_price,
+        position.size,
+        position.side,
+    )?;
+    let (released_collateral, settlement) = calculate_partial_close(
+        position.size,
+        collateral,
+        pnl,
+        closed_size,
+        &market.params,
+    )?;
+
+    // Bankrupt positions are closed by liquidators, not by their stop-loss
+    require!(settlement.bad_debt == 0, PerpetualError::Undercollateralized);
+    require!(
+        ctx.accounts.collateral_vault.amount >= settlement.payout,
+        PerpetualError::InsufficientVaultBalance
+    );
+
+    let keeper_fee = calculate_fee(closed_size, KEEPER_FEE)?.min(settlement.payout);
+    let owner_payout = settlement.payout - keeper_fee;
+
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let collateral_vault = ctx.accounts.collateral_vault.to_account_info();
+
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.keeper_collateral_account.to_account_info(),
+        market,
+        keeper_fee,
+    )?;
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.owner_collateral_account.to_account_info(),
+        market,
+        owner_payout,
+    )?;
+
+    let insurance_share = calculate_fee(settlement.fee_paid, INSURANCE_FEE_SHARE)?;
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.insurance_fund.to_account_info(),
+        market,
+        insurance_share,
+    )?;
+    market.total_fees = market.total_fees
+        .checked_add(settlement.fee_paid - insurance_share)
+        .ok_or(PerpetualError::Overflow)?;
+
+    let realized_pnl = settlement.realized_pnl
+        .checked_sub(keeper_fee as i64)
+        .ok_or(PerpetualError::Overflow)?;
+    position.funding_index = funding_index;
+    position.realized_pnl = position.realized_pnl
+        .checked_add(realized_pnl)
+        .ok_or(PerpetualError::Overflow)?;
+
+    // Only the level that fired is spent; the other one still guards the
+    // remaining size with the same trigger size
+    match trigger {
+        TriggerType::TakeProfit => position.take_profit = None,
+        TriggerType::StopLoss => position.stop_loss = None,
+    }
+
+    if is_partial {
+        position.size -= closed_size;
+        position.collateral = collateral - released_collateral;
+        position.liquidation_price = calculate_liquidation_price(
+            position.entry_price,
+            position.size,
+            position.side,
+            position.collateral,
+            0,
+            &market.params,
+        )?;
+    } else {
+        position.is_open = false;
+        position.closed_at = Some(clock.unix_timestamp);
+    }
+
+    emit!(TriggerOrderExecuted {
+        position: position.key(),
+        owner: position.owner,
+        keeper: ctx.accounts.keeper.key(),
+        trigger,
+        exit_price,
+        closed_size,
+        remaining_size: if is_partial { position.size } else { 0 },
+        realized_pnl,
+        funding_payment,
+        fee: settlement.fee_paid,
+        keeper_fee,
+        payout: owner_payout,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TriggerOrderExecuted {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub trigger: TriggerType,
+    pub exit_price: u64,
+    pub closed_size: u64,
+    pub remaining_size: u64,
+    pub realized_pnl: i64,
+    pub funding_payment: i64,
+    pub fee: u64,
+    pub keeper_fee: u64,
+    pub payout: u64,
+    pub timestamp: i64,
+}
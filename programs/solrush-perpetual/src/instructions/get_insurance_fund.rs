@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::{constants::*, errors::PerpetualError, state::{InsuranceFundInfo, PerpMarket}};
+
+#[derive(Accounts)]
+pub struct GetInsuranceFund<'info> {
+    #[account(
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    #[account(address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount)]
+    pub insurance_fund: Account<'info, TokenAccount>,
+}
+
+pub fn handler(ctx: Context<GetInsuranceFund>) -> Result<InsuranceFundInfo> {
+    let market = &ctx.accounts.market;
+
+    Ok(InsuranceFundInfo {
+        insurance_fund: market.insurance_fund,
+        balance: ctx.accounts.insurance_fund.amount,
+        bad_debt_long: market.bad_debt_long,
+        bad_debt_short: market.bad_debt_short,
+        bankruptcy_price_long: market.bankruptcy_price_long,
+        bankruptcy_price_short: market.bankruptcy_price_short,
+    })
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::{constants::*, errors::PerpetualError, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position}, utils::*};
+
+#[derive(Accounts)]
+pub struct IncreasePosition<'info> {
+    #[account(
+        mut,
+        constraint = position.owner == user.key() @ PerpetualError::Unauthorized,
+        constraint = position.is_open @ PerpetualError::PositionClosed,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub user_collateral_account: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(
+    ctx: Context<IncreasePosition>,
+    additional_size: u64,
+    additional_collateral: u64,
+) -> Result<()> {
+    require!(additional_size > 0, PerpetualError::InvalidPositionSize);
+
+    let clock = Clock::get()?;
+    let price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
+    if additional_collateral > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_collateral_account.to_account_info(),
+                    to: ctx.accounts.collateral_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            additional_collateral,
+        )?;
+    }
+
+    // As on open, funding paid by this refresh applies to the existing size only
+    let position = &mut ctx.accounts.position;
+    let market = &mut ctx.accounts.market;
+    market.add_open_interest(position.side, additional_size)?;
+    refresh_funding(&mut ctx.accounts.funding_rate, market, price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let collateral = position.collateral
+        .checked_add(additional_collateral)
+        .ok_or(PerpetualError::Overflow)?;
+    let (collateral, shortfall) = apply_funding(collateral, funding_payment)?;
+    require!(shortfall == 0, PerpetualError::Undercollateralized);
+
+    let size = position.size
+        .checked_add(additional_size)
+        .ok_or(PerpetualError::Overflow)?;
+    let entry_price = calculate_average_entry_price(
+        position.entry_price,
+        position.size,
+        price,
+        additional_size,
+    )?;
+
+    // The grown position has to satisfy the same limits as a new one
+    let leverage = calculate_leverage(size, collateral)?;
+    let required_margin = calculate_required_margin(size, leverage, &market.params)?;
+    require!(collateral >= required_margin, PerpetualError::InsufficientCollateral);
+
+    let health = calculate_position_health(
+        entry_price,
+        price,
+        size,
+        position.side,
+        collateral,
+        0,
+        &market.params,
+    )?;
+    require!(
+        health.equity >= health.initial_margin as i64,
+        PerpetualError::InsufficientCollateral
+    );
+
+    position.size = size;
+    position.entry_price = entry_price;
+    position.collateral = collateral;
+    position.leverage = leverage;
+    position.funding_index = funding_index;
+    position.liquidation_price = health.liquidation_price;
+
+    emit!(PositionIncreased {
+        position: position.key(),
+        owner: position.owner,
+        price,
+        added_size: additional_size,
+        added_collateral: additional_collateral,
+        funding_payment,
+        size,
+        collateral,
+        entry_price,
+        leverage,
+        liquidation_price: health.liquidation_price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PositionIncreased {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub price: u64,
+    pub added_size: u64,
+    pub added_collateral: u64,
+    pub funding_payment: i64,
+    pub size: u64,
+    pub collateral: u64,
+    pub entry_price: u64,
+    pub leverage: u8,
+    pub liquidation_price: u64,
+    pub timestamp: i64,
+}
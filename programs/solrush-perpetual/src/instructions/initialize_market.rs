@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solrush_admin::{constants::ADMIN_STATE_SEED, state::AdminState};
+use crate::{constants::*, errors::PerpetualError, oracle::validate_oracle_config, state::{FundingRate, MarketParams, OracleSource, PerpMarket}};
+
+#[derive(Accounts)]
+pub struct InitializeMarket<'info> {
+    #[account(
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        seeds::program = solrush_admin::ID,
+        constraint = admin_state.admin == admin.key() @ PerpetualError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = PerpMarket::LEN,
+        seeds = [PERP_MARKET_SEED, pair.key().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Trading pair reference
+    pub pair: UncheckedAccount<'info>,
+
+    pub collateral_mint: Account<'info, Mint>,
+
+    /// Holds every position's collateral; owned by the market PDA
+    #[account(
+        init,
+        payer = admin,
+        seeds = [COLLATERAL_VAULT_SEED, market.key().as_ref()],
+        bump,
+        token::mint = collateral_mint,
+        token::authority = market,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    /// Backstop for bad debt, fed by a share of trading and liquidation fees
+    #[account(
+        init,
+        payer = admin,
+        seeds = [INSURANCE_FUND_SEED, market.key().as_ref()],
+        bump,
+        token::mint = collateral_mint,
+        token::authority = market,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = FundingRate::LEN,
+        seeds = [FUNDING_RATE_SEED, pair.key().as_ref()],
+        bump
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeMarket>,
+    oracle: Pubkey,
+    oracle_source: OracleSource,
+    max_oracle_staleness: i64,
+    max_oracle_confidence: u16,
+    params: MarketParams,
+) -> Result<()> {
+    validate_oracle_config(oracle, max_oracle_staleness, max_oracle_confidence)?;
+    params.validate()?;
+
+    let market = &mut ctx.accounts.market;
+    let clock = Clock::get()?;
+
+    market.bump = ctx.bumps.market;
+    market.pair = ctx.accounts.pair.key();
+    market.oracle = oracle;
+    market.oracle_source = oracle_source;
+    market.max_oracle_staleness = max_oracle_staleness;
+    market.max_oracle_confidence = max_oracle_confidence;
+    market.params = params;
+    market.is_paused = false;
+    market.collateral_mint = ctx.accounts.collateral_mint.key();
+    market.collateral_vault = ctx.accounts.collateral_vault.key();
+    market.insurance_fund = ctx.accounts.insurance_fund.key();
+    market.open_interest_long = 0;
+    market.open_interest_short = 0;
+    market.total_fees = 0;
+    market.bad_debt_long = 0;
+    market.bad_debt_short = 0;
+    market.bankruptcy_price_long = 0;
+    market.bankruptcy_price_short = 0;
+    market.created_at = clock.unix_timestamp;
+
+    let funding_rate = &mut ctx.accounts.funding_rate;
+    funding_rate.bump = ctx.bumps.funding_rate;
+    funding_rate.pair = market.pair;
+    funding_rate.current_rate = 0;
+    funding_rate.last_update = clock.unix_timestamp;
+    funding_rate.cumulative_funding = 0;
+    funding_rate.last_premium = 0;
+    funding_rate.premium_sum = 0;
+    funding_rate.interval_start = clock.unix_timestamp;
+    funding_rate.last_mark_price = 0;
+    funding_rate.last_index_price = 0;
+    funding_rate.socialized_loss_long = 0;
+    funding_rate.socialized_loss_short = 0;
+
+    emit!(MarketInitialized {
+        market: market.key(),
+        pair: market.pair,
+        oracle,
+        oracle_source,
+        collateral_mint: market.collateral_mint,
+        params,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketInitialized {
+    pub market: Pubkey,
+    pub pair: Pubkey,
+    pub oracle: Pubkey,
+    pub oracle_source: OracleSource,
+    pub collateral_mint: Pubkey,
+    pub params: MarketParams,
+    pub timestamp: i64,
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::{constants::*, state::TraderAccount};
+
+#[derive(Accounts)]
+pub struct InitializeTraderAccount<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = TraderAccount::LEN,
+        seeds = [TRADER_ACCOUNT_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub trader_account: Account<'info, TraderAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeTraderAccount>) -> Result<()> {
+    let trader_account = &mut ctx.accounts.trader_account;
+
+    trader_account.bump = ctx.bumps.trader_account;
+    trader_account.owner = ctx.accounts.user.key();
+    trader_account.next_position_id = 0;
+
+    msg!("Trader account initialized for {}", trader_account.owner);
+
+    Ok(())
+}
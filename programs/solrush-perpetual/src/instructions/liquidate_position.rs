@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::{errors::PerpetualError, state::{Position, PnLInfo}, utils::*};
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::*, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position}, utils::*};
 
 #[derive(Accounts)]
 pub struct LiquidatePosition<'info> {
@@ -9,33 +10,188 @@ pub struct LiquidatePosition<'info> {
     )]
     pub position: Account<'info, Position>,
 
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = market.insurance_fund @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub insurance_fund: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = liquidator_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub liquidator_collateral_account: Account<'info, TokenAccount>,
+
+    /// Receives what is left of the collateral on a full liquidation
+    #[account(
+        mut,
+        constraint = owner_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+        constraint = owner_collateral_account.owner == position.owner @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub owner_collateral_account: Account<'info, TokenAccount>,
+
     pub liquidator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 pub fn handler(ctx: Context<LiquidatePosition>) -> Result<()> {
+    let clock = Clock::get()?;
+    let current_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
     let position = &mut ctx.accounts.position;
+    let market = &mut ctx.accounts.market;
+    refresh_funding(&mut ctx.accounts.funding_rate, market, current_price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+
+    let health = calculate_position_health(
+        position.entry_price,
+        current_price,
+        position.size,
+        position.side,
+        position.collateral,
+        funding_payment,
+        &market.params,
+    )?;
+    require!(health.is_liquidatable, PerpetualError::NotLiquidatable);
 
-    // Mock current price (use oracle in production)
-    let current_price = 9500u64;
+    // Settle accrued funding first; whatever the collateral cannot cover is bad debt
+    let (collateral, funding_shortfall) = apply_funding(position.collateral, funding_payment)?;
+    position.funding_index = funding_index;
 
-    let should_liq = should_liquidate(
+    let pnl = calculate_unrealized_pnl(
+        position.entry_price,
         current_price,
-        position.liquidation_price,
+        position.size,
         position.side,
+    )?;
+    let liquidation = calculate_liquidation(position.size, collateral, pnl, &market.params)?;
+    let is_partial = liquidation.closed_size < position.size;
+
+    let owner_payout = if is_partial { 0 } else { liquidation.remaining_collateral };
+    require!(
+        ctx.accounts.collateral_vault.amount >= liquidation.liquidator_fee + owner_payout,
+        PerpetualError::InsufficientVaultBalance
     );
 
-    require!(should_liq, PerpetualError::NotLiquidatable);
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let collateral_vault = ctx.accounts.collateral_vault.to_account_info();
+    let insurance_fund = ctx.accounts.insurance_fund.to_account_info();
+
+    // The liquidation fee is split between the liquidator and the insurance fund
+    let insurance_share = calculate_fee(liquidation.liquidator_fee, INSURANCE_LIQUIDATION_SHARE)?;
+    let liquidator_reward = liquidation.liquidator_fee - insurance_share;
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.liquidator_collateral_account.to_account_info(),
+        market,
+        liquidator_reward,
+    )?;
+    transfer_from_market(&token_program, &collateral_vault, &insurance_fund, market, insurance_share)?;
+    transfer_from_market(
+        &token_program,
+        &collateral_vault,
+        &ctx.accounts.owner_collateral_account.to_account_info(),
+        market,
+        owner_payout,
+    )?;
+
+    let bad_debt = funding_shortfall
+        .checked_add(liquidation.bad_debt)
+        .ok_or(PerpetualError::Overflow)?;
+    if bad_debt > 0 {
+        let bankruptcy_price = calculate_bankruptcy_price(
+            position.entry_price,
+            position.size,
+            collateral,
+            position.side,
+        )?;
+        absorb_bad_debt(
+            &token_program,
+            &insurance_fund,
+            ctx.accounts.insurance_fund.amount,
+            &collateral_vault,
+            market,
+            bad_debt,
+            position.side,
+            bankruptcy_price,
+        )?;
+    }
+
+    market.remove_open_interest(position.side, liquidation.closed_size);
+    refresh_funding(&mut ctx.accounts.funding_rate, market, current_price, clock.unix_timestamp)?;
 
-    position.is_open = false;
-    position.closed_at = Some(Clock::get()?.unix_timestamp);
+    position.realized_pnl = position.realized_pnl
+        .checked_add(liquidation.realized_pnl)
+        .ok_or(PerpetualError::Overflow)?;
+    position.collateral = liquidation.remaining_collateral - owner_payout;
+    position.size -= liquidation.closed_size;
+    if is_partial {
+        position.liquidation_price = calculate_liquidation_price(
+            position.entry_price,
+            position.size,
+            position.side,
+            position.collateral,
+            0,
+            &market.params,
+        )?;
+    } else {
+        position.is_open = false;
+        position.closed_at = Some(clock.unix_timestamp);
+    }
 
     emit!(PositionLiquidated {
         position: position.key(),
         owner: position.owner,
         liquidator: ctx.accounts.liquidator.key(),
-        liquidation_price: position.liquidation_price,
+        liquidation_price: health.liquidation_price,
+        margin_ratio: health.margin_ratio,
         current_price,
-        timestamp: Clock::get()?.unix_timestamp,
+        funding_payment,
+        closed_size: liquidation.closed_size,
+        remaining_size: position.size,
+        realized_pnl: liquidation.realized_pnl,
+        liquidator_fee: liquidator_reward,
+        insurance_fee: insurance_share,
+        owner_payout,
+        bad_debt,
+        is_partial,
+        timestamp: clock.unix_timestamp,
     });
 
     Ok(())
@@ -47,6 +203,16 @@ pub struct PositionLiquidated {
     pub owner: Pubkey,
     pub liquidator: Pubkey,
     pub liquidation_price: u64,
+    pub margin_ratio: u64,
     pub current_price: u64,
+    pub funding_payment: i64,
+    pub closed_size: u64,
+    pub remaining_size: u64,
+    pub realized_pnl: i64,
+    pub liquidator_fee: u64,
+    pub insurance_fee: u64,
+    pub owner_payout: u64,
+    pub bad_debt: u64,
+    pub is_partial: bool,
     pub timestamp: i64,
 }
@@ -1,13 +1,37 @@
+pub mod initialize_market;
+pub mod set_market_oracle;
+pub mod update_market_params;
+pub mod set_market_paused;
+pub mod initialize_trader_account;
 pub mod open_position;
+pub mod increase_position;
+pub mod decrease_position;
 pub mod close_position;
+pub mod set_trigger_orders;
+pub mod execute_trigger_order;
 pub mod add_margin;
+pub mod remove_margin;
 pub mod liquidate_position;
 pub mod calculate_pnl;
 pub mod update_funding_rate;
+pub mod auto_deleverage;
+pub mod get_insurance_fund;
 
+pub use initialize_market::*;
+pub use set_market_oracle::*;
+pub use update_market_params::*;
+pub use set_market_paused::*;
+pub use initialize_trader_account::*;
 pub use open_position::*;
+pub use increase_position::*;
+pub use decrease_position::*;
 pub use close_position::*;
+pub use set_trigger_orders::*;
+pub use execute_trigger_order::*;
 pub use add_margin::*;
+pub use remove_margin::*;
 pub use liquidate_position::*;
 pub use calculate_pnl::*;
 pub use update_funding_rate::*;
+pub use auto_deleverage::*;
+pub use get_insurance_fund::*;
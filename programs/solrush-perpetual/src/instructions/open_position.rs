@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::{constants::*, errors::PerpetualError, state::{Position, PositionSide}, utils::*};
+use crate::{constants::*, errors::PerpetualError, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position, PositionSide, TraderAccount}, utils::*};
 
 #[derive(Accounts)]
 pub struct OpenPosition<'info> {
@@ -11,20 +11,52 @@ pub struct OpenPosition<'info> {
         seeds = [
             POSITION_SEED,
             user.key().as_ref(),
-            &[position_counter],
+            &trader_account.next_position_id.to_le_bytes(),
         ],
         bump
     )]
     pub position: Account<'info, Position>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [TRADER_ACCOUNT_SEED, user.key().as_ref()],
+        bump = trader_account.bump,
+    )]
+    pub trader_account: Account<'info, TraderAccount>,
+
+    /// CHECK: Trading pair reference
+    pub pair: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, pair.key().as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
     pub collateral_vault: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+    )]
     pub user_collateral_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Trading pair reference
-    pub pair: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, pair.key().as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub user: Signer<'info>,
@@ -41,15 +73,30 @@ pub fn handler(
     collateral: u64,
 ) -> Result<()> {
     require!(size > 0, PerpetualError::InvalidPositionSize);
-    require!(leverage >= MIN_LEVERAGE && leverage <= MAX_LEVERAGE, PerpetualError::InvalidLeverage);
 
-    let required_margin = calculate_required_margin(size, leverage)?;
+    let required_margin = calculate_required_margin(size, leverage, &ctx.accounts.market.params)?;
     require!(collateral >= required_margin, PerpetualError::InsufficientCollateral);
 
-    // Mock entry price (in production, use oracle)
-    let entry_price = 10000u64;
+    let clock = Clock::get()?;
+    let entry_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
 
-    let liquidation_price = calculate_liquidation_price(entry_price, leverage, side)?;
+    // Opening costs are charged on close, so the new position must already
+    // cover them on top of maintenance margin
+    let health = calculate_position_health(
+        entry_price,
+        entry_price,
+        size,
+        side,
+        collateral,
+        0,
+        &ctx.accounts.market.params,
+    )?;
+    require!(!health.is_liquidatable, PerpetualError::InsufficientCollateral);
+    let liquidation_price = health.liquidation_price;
 
     // Transfer collateral to vault
     token::transfer(
@@ -64,11 +111,23 @@ pub fn handler(
         collateral,
     )?;
 
+    // Sample the new skew before snapshotting so any funding paid now only
+    // applies to positions that were already open
+    let market = &mut ctx.accounts.market;
+    market.add_open_interest(side, size)?;
+    refresh_funding(&mut ctx.accounts.funding_rate, market, entry_price, clock.unix_timestamp)?;
+
+    let trader_account = &mut ctx.accounts.trader_account;
+    let position_id = trader_account.next_position_id;
+    trader_account.next_position_id = position_id
+        .checked_add(1)
+        .ok_or(PerpetualError::Overflow)?;
+
     let position = &mut ctx.accounts.position;
-    let clock = Clock::get()?;
 
     position.bump = ctx.bumps.position;
     position.owner = ctx.accounts.user.key();
+    position.position_id = position_id;
     position.pair = ctx.accounts.pair.key();
     position.side = side;
     position.size = size;
@@ -78,14 +137,17 @@ pub fn handler(
     position.liquidation_price = liquidation_price;
     position.take_profit = None;
     position.stop_loss = None;
-    position.funding_index = 0;
+    position.trigger_size = 0;
+    position.funding_index = ctx.accounts.funding_rate.funding_index(side);
     position.is_open = true;
+    position.realized_pnl = 0;
     position.opened_at = clock.unix_timestamp;
     position.closed_at = None;
 
     emit!(PositionOpened {
         position: position.key(),
         owner: position.owner,
+        position_id,
         side,
         size,
         leverage,
@@ -102,6 +164,7 @@ pub fn handler(
 pub struct PositionOpened {
     pub position: Pubkey,
     pub owner: Pubkey,
+    pub position_id: u64,
     pub side: PositionSide,
     pub size: u64,
     pub leverage: u8,
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{constants::*, errors::PerpetualError, insurance::transfer_from_market, oracle::get_oracle_price, state::{FundingRate, PerpMarket, Position}, utils::*};
+
+#[derive(Accounts)]
+pub struct RemoveMargin<'info> {
+    #[account(
+        mut,
+        constraint = position.owner == user.key() @ PerpetualError::Unauthorized,
+        constraint = position.is_open @ PerpetualError::PositionClosed,
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        seeds = [PERP_MARKET_SEED, position.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, position.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    #[account(
+        mut,
+        address = market.collateral_vault @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub collateral_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_collateral_account.mint == market.collateral_mint @ PerpetualError::InvalidCollateralAccount,
+        constraint = user_collateral_account.owner == user.key() @ PerpetualError::InvalidCollateralAccount,
+    )]
+    pub user_collateral_account: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<RemoveMargin>, amount: u64) -> Result<()> {
+    require!(amount > 0, PerpetualError::InsufficientCollateral);
+
+    let clock = Clock::get()?;
+    let current_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
+
+    // Settle funding accrued up to now before measuring what can be withdrawn
+    let position = &mut ctx.accounts.position;
+    refresh_funding(&mut ctx.accounts.funding_rate, &ctx.accounts.market, current_price, clock.unix_timestamp)?;
+    let funding_index = ctx.accounts.funding_rate.funding_index(position.side);
+    let funding_payment = calculate_funding_payment(
+        position.size,
+        position.side,
+        position.funding_index,
+        funding_index,
+    )?;
+    let (collateral, shortfall) = apply_funding(position.collateral, funding_payment)?;
+    require!(shortfall == 0, PerpetualError::Undercollateralized);
+
+    let collateral = collateral
+        .checked_sub(amount)
+        .ok_or(PerpetualError::InsufficientCollateral)?;
+
+    // Unrealized profit counts toward the margin but is never withdrawn
+    // itself, and the collateral alone must stay within the max leverage
+    let params = &ctx.accounts.market.params;
+    let health = calculate_position_health(
+        position.entry_price,
+        current_price,
+        position.size,
+        position.side,
+        collateral,
+        0,
+        params,
+    )?;
+    require!(
+        health.equity >= health.initial_margin as i64,
+        PerpetualError::InsufficientCollateral
+    );
+    let leverage = calculate_withdrawal_leverage(position.size, collateral, params)?;
+    require!(
+        ctx.accounts.collateral_vault.amount >= amount,
+        PerpetualError::InsufficientVaultBalance
+    );
+
+    transfer_from_market(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.collateral_vault.to_account_info(),
+        &ctx.accounts.user_collateral_account.to_account_info(),
+        &ctx.accounts.market,
+        amount,
+    )?;
+
+    position.collateral = collateral;
+    position.leverage = leverage;
+    position.funding_index = funding_index;
+    position.liquidation_price = health.liquidation_price;
+
+    emit!(MarginRemoved {
+        position: position.key(),
+        amount,
+        funding_payment,
+        new_collateral: collateral,
+        leverage,
+        liquidation_price: health.liquidation_price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarginRemoved {
+    pub position: Pubkey,
+    pub amount: u64,
+    pub funding_payment: i64,
+    pub new_collateral: u64,
+    pub leverage: u8,
+    pub liquidation_price: u64,
+    pub timestamp: i64,
+}
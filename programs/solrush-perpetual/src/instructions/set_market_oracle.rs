@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use solrush_admin::{constants::ADMIN_STATE_SEED, state::AdminState};
+use crate::{constants::*, errors::PerpetualError, oracle::validate_oracle_config, state::{OracleSource, PerpMarket}};
+
+#[derive(Accounts)]
+pub struct SetMarketOracle<'info> {
+    #[account(
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        seeds::program = solrush_admin::ID,
+        constraint = admin_state.admin == admin.key() @ PerpetualError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<SetMarketOracle>,
+    oracle: Pubkey,
+    oracle_source: OracleSource,
+    max_oracle_staleness: i64,
+    max_oracle_confidence: u16,
+) -> Result<()> {
+    validate_oracle_config(oracle, max_oracle_staleness, max_oracle_confidence)?;
+
+    let market = &mut ctx.accounts.market;
+    market.oracle = oracle;
+    market.oracle_source = oracle_source;
+    market.max_oracle_staleness = max_oracle_staleness;
+    market.max_oracle_confidence = max_oracle_confidence;
+
+    emit!(MarketOracleUpdated {
+        market: market.key(),
+        oracle,
+        oracle_source,
+        max_oracle_staleness,
+        max_oracle_confidence,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketOracleUpdated {
+    pub market: Pubkey,
+    pub oracle: Pubkey,
+    pub oracle_source: OracleSource,
+    pub max_oracle_staleness: i64,
+    pub max_oracle_confidence: u16,
+    pub timestamp: i64,
+}
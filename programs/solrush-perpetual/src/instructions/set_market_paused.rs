@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use solrush_admin::{constants::ADMIN_STATE_SEED, state::AdminState};
+use crate::{constants::*, errors::PerpetualError, state::{FundingRate, PerpMarket}};
+
+#[derive(Accounts)]
+pub struct SetMarketPaused<'info> {
+    #[account(
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        seeds::program = solrush_admin::ID,
+        constraint = admin_state.admin == admin.key() @ PerpetualError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    #[account(
+        mut,
+        seeds = [FUNDING_RATE_SEED, market.pair.as_ref()],
+        bump = funding_rate.bump,
+    )]
+    pub funding_rate: Account<'info, FundingRate>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMarketPaused>, is_paused: bool) -> Result<()> {
+    let market = &mut ctx.accounts.market;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Funding does not accrue while paused, so a resumed market starts a
+    // fresh interval instead of charging the last premium for the pause
+    if market.is_paused && !is_paused {
+        let funding_rate = &mut ctx.accounts.funding_rate;
+        funding_rate.premium_sum = 0;
+        funding_rate.last_update = now;
+        funding_rate.interval_start = now;
+    }
+    market.is_paused = is_paused;
+
+    emit!(MarketPauseUpdated {
+        market: market.key(),
+        is_paused,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketPauseUpdated {
+    pub market: Pubkey,
+    pub is_paused: bool,
+    pub timestamp: i64,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::{errors::PerpetualError, state::Position, utils::*};
+
+#[derive(Accounts)]
+pub struct SetTriggerOrders<'info> {
+    #[account(
+        mut,
+        constraint = position.owner == user.key() @ PerpetualError::Unauthorized,
+        constraint = position.is_open @ PerpetualError::PositionClosed,
+    )]
+    pub position: Account<'info, Position>,
+
+    pub user: Signer<'info>,
+}
+
+/// Replace both levels; `None` clears a level
+pub fn handler(
+    ctx: Context<SetTriggerOrders>,
+    take_profit: Option<u64>,
+    stop_loss: Option<u64>,
+    trigger_size: u64,
+) -> Result<()> {
+    let position = &mut ctx.accounts.position;
+    validate_trigger_prices(position.side, take_profit, stop_loss)?;
+
+    position.take_profit = take_profit;
+    position.stop_loss = stop_loss;
+    position.trigger_size = trigger_size;
+
+    emit!(TriggerOrdersUpdated {
+        position: position.key(),
+        owner: position.owner,
+        take_profit,
+        stop_loss,
+        trigger_size,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TriggerOrdersUpdated {
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub take_profit: Option<u64>,
+    pub stop_loss: Option<u64>,
+    pub trigger_size: u64,
+    pub timestamp: i64,
+}
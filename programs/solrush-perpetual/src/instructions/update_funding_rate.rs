@@ -1,31 +1,47 @@
 use anchor_lang::prelude::*;
-use crate::{constants::*, state::FundingRate};
+use crate::{constants::*, errors::PerpetualError, oracle::get_oracle_price, state::{FundingRate, PerpMarket}, utils::refresh_funding};
 
+/// Permissionless funding crank
 #[derive(Accounts)]
 pub struct UpdateFundingRate<'info> {
+    #[account(
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+        constraint = !market.is_paused @ PerpetualError::MarketPaused,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    /// CHECK: Price account configured on the market, parsed by `get_oracle_price`
+    #[account(address = market.oracle @ PerpetualError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [FUNDING_RATE_SEED, funding_rate.pair.as_ref()],
+        seeds = [FUNDING_RATE_SEED, market.pair.as_ref()],
         bump = funding_rate.bump,
     )]
     pub funding_rate: Account<'info, FundingRate>,
-
-    pub admin: Signer<'info>,
 }
 
-pub fn handler(ctx: Context<UpdateFundingRate>, new_rate: i64) -> Result<()> {
-    let funding_rate = &mut ctx.accounts.funding_rate;
+pub fn handler(ctx: Context<UpdateFundingRate>) -> Result<()> {
     let clock = Clock::get()?;
+    let index_price = get_oracle_price(
+        &ctx.accounts.oracle.to_account_info(),
+        &ctx.accounts.market,
+        clock.unix_timestamp,
+    )?;
 
-    funding_rate.current_rate = new_rate;
-    funding_rate.last_update = clock.unix_timestamp;
-    funding_rate.cumulative_funding = funding_rate.cumulative_funding
-        .checked_add(new_rate)
-        .unwrap_or(funding_rate.cumulative_funding);
+    let funding_rate = &mut ctx.accounts.funding_rate;
+    let funding_paid = refresh_funding(funding_rate, &ctx.accounts.market, index_price, clock.unix_timestamp)?;
 
     emit!(FundingRateUpdated {
         pair: funding_rate.pair,
-        new_rate,
+        mark_price: funding_rate.last_mark_price,
+        index_price,
+        premium: funding_rate.last_premium,
+        funding_paid,
+        current_rate: funding_rate.current_rate,
+        cumulative_funding: funding_rate.cumulative_funding,
         timestamp: clock.unix_timestamp,
     });
 
@@ -35,6 +51,11 @@ pub fn handler(ctx: Context<UpdateFundingRate>, new_rate: i64) -> Result<()> {
 #[event]
 pub struct FundingRateUpdated {
     pub pair: Pubkey,
-    pub new_rate: i64,
+    pub mark_price: u64,
+    pub index_price: u64,
+    pub premium: i64,
+    pub funding_paid: Option<i64>,
+    pub current_rate: i64,
+    pub cumulative_funding: i64,
     pub timestamp: i64,
 }
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use solrush_admin::{constants::ADMIN_STATE_SEED, state::AdminState};
+use crate::{constants::*, errors::PerpetualError, state::{MarketParams, PerpMarket}};
+
+#[derive(Accounts)]
+pub struct UpdateMarketParams<'info> {
+    #[account(
+        seeds = [ADMIN_STATE_SEED],
+        bump = admin_state.bump,
+        seeds::program = solrush_admin::ID,
+        constraint = admin_state.admin == admin.key() @ PerpetualError::Unauthorized,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    #[account(
+        mut,
+        seeds = [PERP_MARKET_SEED, market.pair.as_ref()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, PerpMarket>,
+
+    pub admin: Signer<'info>,
+}
+
+/// New limits apply to later opens and margin checks; existing positions
+/// keep their size even if it now exceeds the open interest cap
+pub fn handler(ctx: Context<UpdateMarketParams>, params: MarketParams) -> Result<()> {
+    params.validate()?;
+
+    let market = &mut ctx.accounts.market;
+    market.params = params;
+
+    emit!(MarketParamsUpdated {
+        market: market.key(),
+        params,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MarketParamsUpdated {
+    pub market: Pubkey,
+    pub params: MarketParams,
+    pub timestamp: i64,
+}
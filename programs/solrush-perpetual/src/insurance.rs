@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Transfer};
+use crate::{constants::PERP_MARKET_SEED, state::{PerpMarket, PositionSide}};
+
+/// Transfer `amount` out of a token account owned by the market PDA
+pub fn transfer_from_market<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    market: &Account<'info, PerpMarket>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let market_seeds = &[
+        PERP_MARKET_SEED,
+        market.pair.as_ref(),
+        &[market.bump],
+    ];
+    let signer = &[&market_seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                authority: market.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )
+}
+
+/// Cover `bad_debt` left by a bankrupt `side` position from the insurance fund
+///
+/// Whatever the fund cannot cover stays on the market as bad debt of `side`
+/// and makes positions opposite it eligible for auto-deleveraging near
+/// `bankruptcy_price`. Returns the uncovered amount.
+#[allow(clippy::too_many_arguments)]
+pub fn absorb_bad_debt<'info>(
+    token_program: &AccountInfo<'info>,
+    insurance_fund: &AccountInfo<'info>,
+    insurance_fund_balance: u64,
+    collateral_vault: &AccountInfo<'info>,
+    market: &mut Account<'info, PerpMarket>,
+    bad_debt: u64,
+    side: PositionSide,
+    bankruptcy_price: u64,
+) -> Result<u64> {
+    if bad_debt == 0 {
+        return Ok(0);
+    }
+
+    let covered = bad_debt.min(insurance_fund_balance);
+    transfer_from_market(token_program, insurance_fund, collateral_vault, market, covered)?;
+
+    let uncovered = bad_debt - covered;
+    if uncovered > 0 {
+        market.add_bad_debt(side, uncovered, bankruptcy_price)?;
+    }
+
+    emit!(BadDebtAbsorbed {
+        market: market.key(),
+        side,
+        bad_debt,
+        covered_by_insurance: covered,
+        uncovered,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(uncovered)
+}
+
+#[event]
+pub struct BadDebtAbsorbed {
+    pub market: Pubkey,
+    pub side: PositionSide,
+    pub bad_debt: u64,
+    pub covered_by_insurance: u64,
+    pub uncovered: u64,
+    pub timestamp: i64,
+}
@@ -5,12 +5,16 @@ declare_id!("SRPerp1111111111111111111111111111111111111");
 pub mod constants;
 pub mod errors;
 pub mod instructions;
+pub mod insurance;
+pub mod oracle;
 pub mod state;
 pub mod utils;
 
 pub use constants::*;
 pub use errors::*;
 pub use instructions::*;
+pub use insurance::*;
+pub use oracle::*;
 pub use state::*;
 pub use utils::*;
 
@@ -18,7 +22,73 @@ pub use utils::*;
 pub mod solrush_perpetual {
     use super::*;
 
-    /// Open a leveraged position
+    /// Create the configuration account of a perp market (admin only)
+    ///
+    /// # Arguments
+    /// * `oracle` - Pyth or Switchboard price account quoting the market
+    /// * `oracle_source` - Format the oracle account is read as
+    /// * `max_oracle_staleness` - Maximum age of an oracle price in seconds
+    /// * `max_oracle_confidence` - Maximum confidence interval in basis points of the price
+    /// * `params` - Leverage, margin, open interest and fee settings
+    pub fn initialize_market(
+        ctx: Context<InitializeMarket>,
+        oracle: Pubkey,
+        oracle_source: OracleSource,
+        max_oracle_staleness: i64,
+        max_oracle_confidence: u16,
+        params: MarketParams,
+    ) -> Result<()> {
+        instructions::initialize_market::handler(
+            ctx,
+            oracle,
+            oracle_source,
+            max_oracle_staleness,
+            max_oracle_confidence,
+            params,
+        )
+    }
+
+    /// Replace a market's oracle and its validation limits (admin only)
+    pub fn set_market_oracle(
+        ctx: Context<SetMarketOracle>,
+        oracle: Pubkey,
+        oracle_source: OracleSource,
+        max_oracle_staleness: i64,
+        max_oracle_confidence: u16,
+    ) -> Result<()> {
+        instructions::set_market_oracle::handler(
+            ctx,
+            oracle,
+            oracle_source,
+            max_oracle_staleness,
+            max_oracle_confidence,
+        )
+    }
+
+    /// Replace a market's leverage, margin, open interest and fee settings (admin only)
+    pub fn update_market_params(ctx: Context<UpdateMarketParams>, params: MarketParams) -> Result<()> {
+        instructions::update_market_params::handler(ctx, params)
+    }
+
+    /// Pause or resume trading, funding and liquidations on a market (admin only)
+    pub fn set_market_paused(ctx: Context<SetMarketPaused>, is_paused: bool) -> Result<()> {
+        instructions::set_market_paused::handler(ctx, is_paused)
+    }
+
+    /// Create the account that numbers a trader's positions
+    pub fn initialize_trader_account(ctx: Context<InitializeTraderAccount>) -> Result<()> {
+        instructions::initialize_trader_account::handler(ctx)
+    }
+
+    /// Open a leveraged position at the market's oracle price
+    ///
+    /// The position is seeded by the trader account's next position id.
+    ///
+    /// # Arguments
+    /// * `side` - Long or Short
+    /// * `size` - Position notional in collateral units
+    /// * `leverage` - Within the market's leverage range
+    /// * `collateral` - Collateral deposited into the vault
     pub fn open_position(
         ctx: Context<OpenPosition>,
         side: PositionSide,
@@ -29,16 +99,62 @@ pub mod solrush_perpetual {
         instructions::open_position::handler(ctx, side, size, leverage, collateral)
     }
 
+    /// Add size to a position at the oracle price, averaging its entry price
+    ///
+    /// # Arguments
+    /// * `additional_size` - Notional added to the position
+    /// * `additional_collateral` - Collateral deposited alongside, may be 0
+    pub fn increase_position(
+        ctx: Context<IncreasePosition>,
+        additional_size: u64,
+        additional_collateral: u64,
+    ) -> Result<()> {
+        instructions::increase_position::handler(ctx, additional_size, additional_collateral)
+    }
+
+    /// Close part of a position at the oracle price, paying out its share of
+    /// collateral and PnL
+    pub fn decrease_position(ctx: Context<DecreasePosition>, closed_size: u64) -> Result<()> {
+        instructions::decrease_position::handler(ctx, closed_size)
+    }
+
     /// Close an existing position
     pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
         instructions::close_position::handler(ctx)
     }
 
+    /// Set or clear a position's take-profit and stop-loss levels
+    ///
+    /// # Arguments
+    /// * `take_profit` - Price to take profit at, `None` to clear
+    /// * `stop_loss` - Price to stop losses at, `None` to clear
+    /// * `trigger_size` - Size closed when a level is crossed, 0 for all of it
+    pub fn set_trigger_orders(
+        ctx: Context<SetTriggerOrders>,
+        take_profit: Option<u64>,
+        stop_loss: Option<u64>,
+        trigger_size: u64,
+    ) -> Result<()> {
+        instructions::set_trigger_orders::handler(ctx, take_profit, stop_loss, trigger_size)
+    }
+
+    /// Close a position whose take-profit or stop-loss the oracle price has
+    /// crossed, paying the keeper `KEEPER_FEE` (permissionless)
+    pub fn execute_trigger_order(ctx: Context<ExecuteTriggerOrder>) -> Result<()> {
+        instructions::execute_trigger_order::handler(ctx)
+    }
+
     /// Add margin to an existing position
     pub fn add_margin(ctx: Context<AddMargin>, additional_collateral: u64) -> Result<()> {
         instructions::add_margin::handler(ctx, additional_collateral)
     }
 
+    /// Withdraw collateral from a position that stays above initial margin
+    /// after unrealized PnL and accrued funding
+    pub fn remove_margin(ctx: Context<RemoveMargin>, amount: u64) -> Result<()> {
+        instructions::remove_margin::handler(ctx, amount)
+    }
+
     /// Liquidate an undercollateralized position
     pub fn liquidate_position(ctx: Context<LiquidatePosition>) -> Result<()> {
         instructions::liquidate_position::handler(ctx)
@@ -49,8 +165,19 @@ pub mod solrush_perpetual {
         instructions::calculate_pnl::handler(ctx)
     }
 
-    /// Update funding rate
-    pub fn update_funding_rate(ctx: Context<UpdateFundingRate>, new_rate: i64) -> Result<()> {
-        instructions::update_funding_rate::handler(ctx, new_rate)
+    /// Sample the mark/index premium and pay funding once per interval (permissionless)
+    pub fn update_funding_rate(ctx: Context<UpdateFundingRate>) -> Result<()> {
+        instructions::update_funding_rate::handler(ctx)
+    }
+
+    /// Charge bad debt the insurance fund cannot cover to the open `side`
+    /// positions opposite it, pro rata of size (permissionless)
+    pub fn auto_deleverage(ctx: Context<AutoDeleverage>, side: PositionSide) -> Result<()> {
+        instructions::auto_deleverage::handler(ctx, side)
+    }
+
+    /// Insurance fund balance and outstanding bad debt of a market
+    pub fn get_insurance_fund(ctx: Context<GetInsuranceFund>) -> Result<InsuranceFundInfo> {
+        instructions::get_insurance_fund::handler(ctx)
     }
 }
@@ -0,0 +1,337 @@
+use anchor_lang::prelude::*;
+use crate::{errors::PerpetualError, state::{OracleSource, PerpMarket}};
+use solrush_oracle::PythError;
+
+pub use solrush_oracle::{MAX_CLOCK_SKEW, PYTH_ACCOUNT_TYPE_PRICE, PYTH_MAGIC, PYTH_STATUS_TRADING};
+
+/// Decimals of every normalized perp price
+pub const PRICE_DECIMALS: u32 = 6;
+
+/// Normalized price scaling factor (1e6)
+pub const PRICE_PRECISION: u64 = 1_000_000;
+
+/// Largest accepted oracle staleness a market can be configured with
+pub const MAX_ORACLE_STALENESS: i64 = 300;
+
+/// Anchor discriminator of a Switchboard v2 `AggregatorAccountData`
+pub const SWITCHBOARD_AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Byte offsets of the latest confirmed round in a Switchboard v2 aggregator
+const SWITCHBOARD_NUM_SUCCESS_OFFSET: usize = 341;
+const SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const SWITCHBOARD_RESULT_MANTISSA_OFFSET: usize = 366;
+const SWITCHBOARD_RESULT_SCALE_OFFSET: usize = 382;
+const SWITCHBOARD_STD_DEV_MANTISSA_OFFSET: usize = 386;
+const SWITCHBOARD_STD_DEV_SCALE_OFFSET: usize = 402;
+const SWITCHBOARD_ACCOUNT_MIN_LEN: usize = 406;
+
+/// Oracle price normalized to `PRICE_PRECISION`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: u64,
+    pub confidence: u64,
+    pub publish_time: i64,
+}
+
+impl From<PythError> for PerpetualError {
+    fn from(error: PythError) -> Self {
+        match error {
+            PythError::InvalidAccount => PerpetualError::InvalidOracleAccount,
+            PythError::Stale => PerpetualError::StaleOraclePrice,
+            PythError::FuturePublishTime => PerpetualError::FutureOraclePrice,
+        }
+    }
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| PerpetualError::InvalidOracleAccount.into())
+}
+
+/// Scale `mantissa * 10^expo` to `PRICE_DECIMALS` decimals
+pub fn normalize_price(mantissa: i128, expo: i32) -> Result<u64> {
+    require!(mantissa >= 0, PerpetualError::InvalidPrice);
+
+    let shift = expo
+        .checked_add(PRICE_DECIMALS as i32)
+        .ok_or(PerpetualError::Overflow)?;
+    let scale = 10u128
+        .checked_pow(shift.unsigned_abs())
+        .ok_or(PerpetualError::Overflow)?;
+
+    let price = if shift >= 0 {
+        (mantissa as u128).checked_mul(scale).ok_or(PerpetualError::Overflow)?
+    } else {
+        (mantissa as u128) / scale
+    };
+
+    u64::try_from(price).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Parse the aggregate price from Pyth v2 price account data
+pub fn parse_pyth_price(data: &[u8]) -> Result<OraclePrice> {
+    let pyth_price = solrush_oracle::parse_pyth_price(data).map_err(PerpetualError::from)?;
+    require!(pyth_price.is_trading(), PerpetualError::InvalidOracleAccount);
+
+    Ok(OraclePrice {
+        price: normalize_price(pyth_price.price as i128, pyth_price.expo)?,
+        confidence: normalize_price(pyth_price.conf as i128, pyth_price.expo)?,
+        publish_time: pyth_price.publish_time,
+    })
+}
+
+/// Parse the latest confirmed round from Switchboard v2 aggregator data
+///
+/// The round's standard deviation is used as the confidence interval.
+pub fn parse_switchboard_price(data: &[u8]) -> Result<OraclePrice> {
+    require!(data.len() >= SWITCHBOARD_ACCOUNT_MIN_LEN, PerpetualError::InvalidOracleAccount);
+    require!(
+        data[..8] == SWITCHBOARD_AGGREGATOR_DISCRIMINATOR,
+        PerpetualError::InvalidOracleAccount
+    );
+    require!(
+        u32::from_le_bytes(read_bytes(data, SWITCHBOARD_NUM_SUCCESS_OFFSET)?) > 0,
+        PerpetualError::InvalidOracleAccount
+    );
+
+    let mantissa = i128::from_le_bytes(read_bytes(data, SWITCHBOARD_RESULT_MANTISSA_OFFSET)?);
+    let scale = u32::from_le_bytes(read_bytes(data, SWITCHBOARD_RESULT_SCALE_OFFSET)?);
+    let std_dev_mantissa = i128::from_le_bytes(read_bytes(data, SWITCHBOARD_STD_DEV_MANTISSA_OFFSET)?);
+    let std_dev_scale = u32::from_le_bytes(read_bytes(data, SWITCHBOARD_STD_DEV_SCALE_OFFSET)?);
+
+    let expo = |scale: u32| -> Result<i32> {
+        i32::try_from(scale)
+            .map(|scale| -scale)
+            .map_err(|_| PerpetualError::InvalidOracleAccount.into())
+    };
+
+    Ok(OraclePrice {
+        price: normalize_price(mantissa, expo(scale)?)?,
+        confidence: normalize_price(std_dev_mantissa, expo(std_dev_scale)?)?,
+        publish_time: i64::from_le_bytes(read_bytes(data, SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET)?),
+    })
+}
+
+/// Parse oracle account data in the given format
+pub fn parse_oracle_price(data: &[u8], source: OracleSource) -> Result<OraclePrice> {
+    match source {
+        OracleSource::Pyth => parse_pyth_price(data),
+        OracleSource::Switchboard => parse_switchboard_price(data),
+    }
+}
+
+/// Reject prices that are zero, older than `max_staleness` seconds, published
+/// ahead of the clock, or whose confidence interval exceeds `max_confidence`
+/// basis points of the price
+pub fn validate_oracle_price(
+    oracle_price: &OraclePrice,
+    now: i64,
+    max_staleness: i64,
+    max_confidence: u16,
+) -> Result<()> {
+    require!(oracle_price.price > 0, PerpetualError::InvalidPrice);
+    solrush_oracle::check_publish_time(oracle_price.publish_time, now, max_staleness)
+        .map_err(PerpetualError::from)?;
+
+    let confidence = (oracle_price.confidence as u128)
+        .checked_mul(10000)
+        .ok_or(PerpetualError::Overflow)?
+        / oracle_price.price as u128;
+    require!(
+        confidence <= max_confidence as u128,
+        PerpetualError::OracleConfidenceTooWide
+    );
+
+    Ok(())
+}
+
+/// Validated, normalized price of a market's configured oracle
+pub fn get_oracle_price(oracle: &AccountInfo, market: &PerpMarket, now: i64) -> Result<u64> {
+    require_keys_eq!(oracle.key(), market.oracle, PerpetualError::InvalidOracleAccount);
+
+    let oracle_price = parse_oracle_price(&oracle.try_borrow_data()?, market.oracle_source)?;
+    validate_oracle_price(
+        &oracle_price,
+        now,
+        market.max_oracle_staleness,
+        market.max_oracle_confidence,
+    )?;
+
+    Ok(oracle_price.price)
+}
+
+/// Validate oracle settings supplied by the admin
+pub fn validate_oracle_config(
+    oracle: Pubkey,
+    max_oracle_staleness: i64,
+    max_oracle_confidence: u16,
+) -> Result<()> {
+    require!(oracle != Pubkey::default(), PerpetualError::InvalidOracleConfig);
+    require!(
+        max_oracle_staleness > 0 && max_oracle_staleness <= MAX_ORACLE_STALENESS,
+        PerpetualError::InvalidOracleConfig
+    );
+    require!(
+        max_oracle_confidence > 0 && max_oracle_confidence <= 10000,
+        PerpetualError::InvalidOracleConfig
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::state::MarketParams;
+
+    pub use solrush_oracle::mock_pyth_account;
+
+    pub fn mock_switchboard_account(
+        mantissa: i128,
+        scale: u32,
+        std_dev: i128,
+        round_open_timestamp: i64,
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; SWITCHBOARD_ACCOUNT_MIN_LEN];
+        data[..8].copy_from_slice(&SWITCHBOARD_AGGREGATOR_DISCRIMINATOR);
+        data[SWITCHBOARD_NUM_SUCCESS_OFFSET..SWITCHBOARD_NUM_SUCCESS_OFFSET + 4]
+            .copy_from_slice(&3u32.to_le_bytes());
+        data[SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET..SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&round_open_timestamp.to_le_bytes());
+        data[SWITCHBOARD_RESULT_MANTISSA_OFFSET..SWITCHBOARD_RESULT_MANTISSA_OFFSET + 16]
+            .copy_from_slice(&mantissa.to_le_bytes());
+        data[SWITCHBOARD_RESULT_SCALE_OFFSET..SWITCHBOARD_RESULT_SCALE_OFFSET + 4]
+            .copy_from_slice(&scale.to_le_bytes());
+        data[SWITCHBOARD_STD_DEV_MANTISSA_OFFSET..SWITCHBOARD_STD_DEV_MANTISSA_OFFSET + 16]
+            .copy_from_slice(&std_dev.to_le_bytes());
+        data[SWITCHBOARD_STD_DEV_SCALE_OFFSET..SWITCHBOARD_STD_DEV_SCALE_OFFSET + 4]
+            .copy_from_slice(&scale.to_le_bytes());
+        data
+    }
+
+    pub const MOCK_PARAMS: MarketParams = MarketParams {
+        min_leverage: 1,
+        max_leverage: 5,
+        initial_margin_rate: 2000,     // 20%
+        maintenance_margin_rate: 1000, // 10%
+        max_open_interest: u64::MAX,
+        trading_fee: 10,               // 0.1%
+        liquidation_fee: 200,          // 2%
+    };
+
+    pub fn mock_market(oracle: Pubkey, oracle_source: OracleSource) -> PerpMarket {
+        PerpMarket {
+            bump: 0,
+            pair: Pubkey::default(),
+            oracle,
+            oracle_source,
+            max_oracle_staleness: 60,
+            max_oracle_confidence: 200, // 2%
+            params: MOCK_PARAMS,
+            is_paused: false,
+            collateral_mint: Pubkey::default(),
+            collateral_vault: Pubkey::default(),
+            insurance_fund: Pubkey::default(),
+            open_interest_long: 0,
+            open_interest_short: 0,
+            total_fees: 0,
+            bad_debt_long: 0,
+            bad_debt_short: 0,
+            bankruptcy_price_long: 0,
+            bankruptcy_price_short: 0,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_normalize_price() {
+        assert_eq!(normalize_price(150_00000000, -8).unwrap(), 150 * PRICE_PRECISION);
+        assert_eq!(normalize_price(150_123456789, -9).unwrap(), 150_123456);
+        assert_eq!(normalize_price(150, 0).unwrap(), 150 * PRICE_PRECISION);
+        assert_eq!(normalize_price(15, 1).unwrap(), 150 * PRICE_PRECISION);
+        assert!(normalize_price(-1, -8).is_err());
+        assert!(normalize_price(i64::MAX as i128, 20).is_err());
+    }
+
+    #[test]
+    fn test_parse_pyth_price() {
+        let data = mock_pyth_account(150_00000000, 5_000000, -8, 1_000);
+        let oracle_price = parse_pyth_price(&data).unwrap();
+        assert_eq!(oracle_price.price, 150 * PRICE_PRECISION);
+        assert_eq!(oracle_price.confidence, 50_000);
+        assert_eq!(oracle_price.publish_time, 1_000);
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = 0;
+        assert!(parse_pyth_price(&bad_magic).is_err());
+        assert!(parse_pyth_price(&data[..100]).is_err());
+
+        let mut halted = data.clone();
+        halted[solrush_oracle::PYTH_AGG_STATUS_OFFSET] = 0;
+        assert!(parse_pyth_price(&halted).is_err());
+    }
+
+    #[test]
+    fn test_parse_switchboard_price() {
+        let data = mock_switchboard_account(150_250_000_000, 9, 100_000_000, 2_000);
+        let oracle_price = parse_switchboard_price(&data).unwrap();
+        assert_eq!(oracle_price.price, 150_250_000);
+        assert_eq!(oracle_price.confidence, 100_000);
+        assert_eq!(oracle_price.publish_time, 2_000);
+
+        // Pyth data is not a Switchboard aggregator and vice versa
+        assert!(parse_switchboard_price(&mock_pyth_account(150_00000000, 0, -8, 0)).is_err());
+        assert!(parse_oracle_price(&data, OracleSource::Pyth).is_err());
+        assert!(parse_oracle_price(&data, OracleSource::Switchboard).is_ok());
+
+        let mut no_round = data.clone();
+        no_round[SWITCHBOARD_NUM_SUCCESS_OFFSET] = 0;
+        assert!(parse_switchboard_price(&no_round).is_err());
+    }
+
+    #[test]
+    fn test_validate_oracle_price() {
+        let fresh = parse_pyth_price(&mock_pyth_account(150_00000000, 1_00000000, -8, 1_000)).unwrap();
+        assert!(validate_oracle_price(&fresh, 1_060, 60, 200).is_ok());
+        assert!(validate_oracle_price(&fresh, 1_061, 60, 200).is_err());
+
+        // A publish time past the clock skew allowance is not treated as fresh
+        assert!(validate_oracle_price(&fresh, 1_000 - MAX_CLOCK_SKEW, 60, 200).is_ok());
+        assert!(validate_oracle_price(&fresh, 999 - MAX_CLOCK_SKEW, 60, 200).is_err());
+
+        // 5 / 150 = 3.3% confidence is too wide
+        let uncertain = parse_pyth_price(&mock_pyth_account(150_00000000, 5_00000000, -8, 1_000)).unwrap();
+        assert!(validate_oracle_price(&uncertain, 1_000, 60, 200).is_err());
+        assert!(validate_oracle_price(&uncertain, 1_000, 60, 400).is_ok());
+    }
+
+    #[test]
+    fn test_get_oracle_price() {
+        let oracle_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0u64;
+        let mut data = mock_switchboard_account(98_500_000, 6, 50_000, 1_000);
+        let oracle = AccountInfo::new(&oracle_key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        let market = mock_market(oracle_key, OracleSource::Switchboard);
+        assert_eq!(get_oracle_price(&oracle, &market, 1_010).unwrap(), 98_500_000);
+        assert!(get_oracle_price(&oracle, &market, 1_100).is_err());
+
+        // Wrong source or an account other than the configured oracle is rejected
+        let pyth_market = mock_market(oracle_key, OracleSource::Pyth);
+        assert!(get_oracle_price(&oracle, &pyth_market, 1_010).is_err());
+        let other_market = mock_market(Pubkey::new_unique(), OracleSource::Switchboard);
+        assert!(get_oracle_price(&oracle, &other_market, 1_010).is_err());
+    }
+
+    #[test]
+    fn test_validate_oracle_config() {
+        let oracle = Pubkey::new_unique();
+        assert!(validate_oracle_config(oracle, 60, 200).is_ok());
+        assert!(validate_oracle_config(Pubkey::default(), 60, 200).is_err());
+        assert!(validate_oracle_config(oracle, 0, 200).is_err());
+        assert!(validate_oracle_config(oracle, MAX_ORACLE_STALENESS + 1, 200).is_err());
+        assert!(validate_oracle_config(oracle, 60, 0).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::{constants::*, errors::PerpetualError, utils::calculate_average_entry_price};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum PositionSide {
@@ -6,31 +7,213 @@ pub enum PositionSide {
     Short,
 }
 
+impl PositionSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            PositionSide::Long => PositionSide::Short,
+            PositionSide::Short => PositionSide::Long,
+        }
+    }
+}
+
+/// Which level of a position triggered its close
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TriggerType {
+    TakeProfit,
+    StopLoss,
+}
+
+/// Price account format a market's oracle is read as
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OracleSource {
+    Pyth,
+    Switchboard,
+}
+
+/// Risk and fee limits of a market; rates are basis points of position size
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MarketParams {
+    pub min_leverage: u8,
+    pub max_leverage: u8,
+    pub initial_margin_rate: u16,
+    pub maintenance_margin_rate: u16,
+    /// Cap on the open interest of each side
+    pub max_open_interest: u64,
+    pub trading_fee: u16,
+    pub liquidation_fee: u16,
+}
+
+impl MarketParams {
+    pub const LEN: usize = 1 + 1 + 2 + 2 + 8 + 2 + 2;
+
+    /// Leverage bounds must be reachable with the initial margin, and the
+    /// maintenance margin must sit between the liquidation fee and the initial margin
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            MIN_LEVERAGE <= self.min_leverage
+                && self.min_leverage <= self.max_leverage
+                && self.max_leverage <= MAX_LEVERAGE,
+            PerpetualError::InvalidMarketParams
+        );
+        require!(
+            self.initial_margin_rate as u64 * self.max_leverage as u64 <= 10000,
+            PerpetualError::InvalidMarketParams
+        );
+        require!(
+            self.liquidation_fee < self.maintenance_margin_rate
+                && self.maintenance_margin_rate < self.initial_margin_rate,
+            PerpetualError::InvalidMarketParams
+        );
+        require!(
+            self.trading_fee <= MAX_TRADING_FEE && self.liquidation_fee <= MAX_LIQUIDATION_FEE,
+            PerpetualError::InvalidMarketParams
+        );
+        require!(self.max_open_interest > 0, PerpetualError::InvalidMarketParams);
+        Ok(())
+    }
+}
+
+/// Per-market configuration
+#[account]
+pub struct PerpMarket {
+    pub bump: u8,
+    pub pair: Pubkey,
+    pub oracle: Pubkey,
+    pub oracle_source: OracleSource,
+    pub max_oracle_staleness: i64,
+    pub max_oracle_confidence: u16,
+    pub params: MarketParams,
+    pub is_paused: bool,
+    pub collateral_mint: Pubkey,
+    pub collateral_vault: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub open_interest_long: u64,
+    pub open_interest_short: u64,
+    pub total_fees: u64,
+    // Uncovered bad debt left by bankrupt positions of each side, and its
+    // debt-weighted bankruptcy price
+    pub bad_debt_long: u64,
+    pub bad_debt_short: u64,
+    pub bankruptcy_price_long: u64,
+    pub bankruptcy_price_short: u64,
+    pub created_at: i64,
+}
+
+impl PerpMarket {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 1 + 8 + 2 + MarketParams::LEN + 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    /// Add open interest to a side, up to `params.max_open_interest`
+    pub fn add_open_interest(&mut self, side: PositionSide, size: u64) -> Result<()> {
+        let max_open_interest = self.params.max_open_interest;
+        let open_interest = match side {
+            PositionSide::Long => &mut self.open_interest_long,
+            PositionSide::Short => &mut self.open_interest_short,
+        };
+        *open_interest = open_interest.checked_add(size).ok_or(PerpetualError::Overflow)?;
+        require!(*open_interest <= max_open_interest, PerpetualError::OpenInterestLimitExceeded);
+        Ok(())
+    }
+
+    pub fn remove_open_interest(&mut self, side: PositionSide, size: u64) {
+        let open_interest = match side {
+            PositionSide::Long => &mut self.open_interest_long,
+            PositionSide::Short => &mut self.open_interest_short,
+        };
+        *open_interest = open_interest.saturating_sub(size);
+    }
+
+    pub fn open_interest(&self, side: PositionSide) -> u64 {
+        match side {
+            PositionSide::Long => self.open_interest_long,
+            PositionSide::Short => self.open_interest_short,
+        }
+    }
+
+    /// Uncovered bad debt of bankrupt `side` positions and its bankruptcy price
+    pub fn bad_debt(&self, side: PositionSide) -> (u64, u64) {
+        match side {
+            PositionSide::Long => (self.bad_debt_long, self.bankruptcy_price_long),
+            PositionSide::Short => (self.bad_debt_short, self.bankruptcy_price_short),
+        }
+    }
+
+    pub fn total_bad_debt(&self) -> u64 {
+        self.bad_debt_long.saturating_add(self.bad_debt_short)
+    }
+
+    /// Record bad debt of a bankrupt `side` position; the side's bankruptcy
+    /// price becomes the average weighted by debt, like an entry price by size
+    pub fn add_bad_debt(&mut self, side: PositionSide, amount: u64, bankruptcy_price: u64) -> Result<()> {
+        let (bad_debt, price) = match side {
+            PositionSide::Long => (&mut self.bad_debt_long, &mut self.bankruptcy_price_long),
+            PositionSide::Short => (&mut self.bad_debt_short, &mut self.bankruptcy_price_short),
+        };
+        *price = calculate_average_entry_price(*price, *bad_debt, bankruptcy_price, amount)?;
+        *bad_debt = bad_debt.checked_add(amount).ok_or(PerpetualError::Overflow)?;
+        Ok(())
+    }
+
+    /// Repay up to `amount` of a side's bad debt; its price resets once cleared
+    pub fn repay_bad_debt(&mut self, side: PositionSide, amount: u64) {
+        let (bad_debt, price) = match side {
+            PositionSide::Long => (&mut self.bad_debt_long, &mut self.bankruptcy_price_long),
+            PositionSide::Short => (&mut self.bad_debt_short, &mut self.bankruptcy_price_short),
+        };
+        *bad_debt = bad_debt.saturating_sub(amount);
+        if *bad_debt == 0 {
+            *price = 0;
+        }
+    }
+}
+
+/// Hands out the ids a trader's positions are seeded by
+#[account]
+pub struct TraderAccount {
+    pub bump: u8,
+    pub owner: Pubkey,
+    pub next_position_id: u64,
+}
+
+impl TraderAccount {
+    pub const LEN: usize = 8 + 1 + 32 + 8;
+}
+
 /// Position account
 #[account]
 pub struct Position {
     pub bump: u8,
     pub owner: Pubkey,
+    pub position_id: u64,
     pub pair: Pubkey,
     pub side: PositionSide,
     pub size: u64,
     pub leverage: u8,
     pub collateral: u64,
     pub entry_price: u64,
+    /// As of the last collateral change; `calculate_pnl` reports the current one
     pub liquidation_price: u64,
     pub take_profit: Option<u64>,
     pub stop_loss: Option<u64>,
+    /// Size closed when a take-profit or stop-loss triggers; 0 closes it all
+    pub trigger_size: u64,
+    /// `FundingRate::funding_index` of the side as of the last settlement
     pub funding_index: i64,
     pub is_open: bool,
+    pub realized_pnl: i64,
     pub opened_at: i64,
     pub closed_at: Option<i64>,
 }
 
 impl Position {
-    pub const LEN: usize = 8 + 1 + 32 + 32 + 1 + 8 + 1 + 8 + 8 + 8 + 9 + 9 + 8 + 1 + 8 + 9;
+    pub const LEN: usize = 8 + 1 + 32 + 8 + 32 + 1 + 8 + 1 + 8 + 8 + 8 + 9 + 9 + 8 + 8 + 1 + 8 + 8 + 9;
 }
 
 /// Funding rate state
+///
+/// `cumulative_funding` is what longs have paid per unit of notional since
+/// the market opened (scaled by 1e9); shorts receive the same amount.
+/// `socialized_loss_long`/`short` is the bad debt each side has been charged
+/// per unit of notional by auto-deleveraging, paid lazily like funding.
 #[account]
 pub struct FundingRate {
     pub bump: u8,
@@ -38,10 +221,37 @@ pub struct FundingRate {
     pub current_rate: i64,
     pub last_update: i64,
     pub cumulative_funding: i64,
+    pub last_premium: i64,
+    pub premium_sum: i128,
+    pub interval_start: i64,
+    pub last_mark_price: u64,
+    pub last_index_price: u64,
+    pub socialized_loss_long: i64,
+    pub socialized_loss_short: i64,
 }
 
 impl FundingRate {
-    pub const LEN: usize = 8 + 1 + 32 + 8 + 8 + 8;
+    pub const LEN: usize = 8 + 1 + 32 + 8 + 8 + 8 + 8 + 16 + 8 + 8 + 8 + 8 + 8;
+
+    /// Index a `side` position owes the change of, in the sign of
+    /// `calculate_funding_payment`: funding plus the side's socialized loss
+    pub fn funding_index(&self, side: PositionSide) -> i64 {
+        match side {
+            PositionSide::Long => self.cumulative_funding.saturating_add(self.socialized_loss_long),
+            PositionSide::Short => self.cumulative_funding.saturating_sub(self.socialized_loss_short),
+        }
+    }
+}
+
+/// Insurance fund and bad debt status of a market
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InsuranceFundInfo {
+    pub insurance_fund: Pubkey,
+    pub balance: u64,
+    pub bad_debt_long: u64,
+    pub bad_debt_short: u64,
+    pub bankruptcy_price_long: u64,
+    pub bankruptcy_price_short: u64,
 }
 
 /// PnL information
@@ -50,6 +260,8 @@ pub struct PnLInfo {
     pub unrealized_pnl: i64,
     pub realized_pnl: i64,
     pub funding_payment: i64,
+    pub equity: i64,
+    pub margin_ratio: u64,
     pub liquidation_price: u64,
     pub is_liquidatable: bool,
 }
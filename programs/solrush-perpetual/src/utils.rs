@@ -1,47 +1,52 @@
 use anchor_lang::prelude::*;
-use crate::{constants::*, errors::PerpetualError, state::PositionSide};
+use crate::{constants::*, errors::PerpetualError, state::{FundingRate, MarketParams, PerpMarket, PositionSide, TriggerType}};
 
 /// Calculate required margin for a position
-pub fn calculate_required_margin(size: u64, leverage: u8) -> Result<u64> {
-    require!(leverage >= MIN_LEVERAGE && leverage <= MAX_LEVERAGE, PerpetualError::InvalidLeverage);
+///
+/// The larger of `size / leverage` and the market's initial margin.
+pub fn calculate_required_margin(size: u64, leverage: u8, params: &MarketParams) -> Result<u64> {
+    require!(
+        leverage >= params.min_leverage && leverage <= params.max_leverage,
+        PerpetualError::InvalidLeverage
+    );
     
     let margin = (size as u128)
         .checked_div(leverage as u128)
         .ok_or(PerpetualError::Overflow)? as u64;
     
-    Ok(margin)
+    Ok(margin.max(calculate_fee(size, params.initial_margin_rate as u64)?))
 }
 
-/// Calculate liquidation price
+/// Price at which a position's equity falls to its maintenance margin
+///
+/// Equity counts the collateral net of accrued `funding_payment` and the fee
+/// to close, so deposits, funding and fees all move the price.
 pub fn calculate_liquidation_price(
     entry_price: u64,
-    leverage: u8,
+    size: u64,
     side: PositionSide,
+    collateral: u64,
+    funding_payment: i64,
+    params: &MarketParams,
 ) -> Result<u64> {
-    let leverage_ratio = leverage as u128;
-    
-    let liq_price = match side {
-        PositionSide::Long => {
-            // Long: liq_price = entry_price * (1 - 1/leverage)
-            let factor = (leverage_ratio - 1) * 10000 / leverage_ratio;
-            (entry_price as u128)
-                .checked_mul(factor)
-                .ok_or(PerpetualError::Overflow)?
-                .checked_div(10000)
-                .ok_or(PerpetualError::Overflow)? as u64
-        }
-        PositionSide::Short => {
-            // Short: liq_price = entry_price * (1 + 1/leverage)
-            let factor = (leverage_ratio + 1) * 10000 / leverage_ratio;
-            (entry_price as u128)
-                .checked_mul(factor)
-                .ok_or(PerpetualError::Overflow)?
-                .checked_div(10000)
-                .ok_or(PerpetualError::Overflow)? as u64
-        }
+    require!(size > 0, PerpetualError::InvalidPositionSize);
+
+    let maintenance_margin = calculate_fee(size, params.maintenance_margin_rate as u64)?;
+    let closing_fee = calculate_fee(size, params.trading_fee as u64)?;
+    let net_collateral = (collateral as i128) - (funding_payment as i128) - (closing_fee as i128);
+
+    // Long:  net + size * (p - entry) / entry = maintenance
+    // Short: net + size * (entry - p) / entry = maintenance
+    let numerator = match side {
+        PositionSide::Long => (size as i128) + (maintenance_margin as i128) - net_collateral,
+        PositionSide::Short => (size as i128) + net_collateral - (maintenance_margin as i128),
     };
-    
-    Ok(liq_price)
+    let price = (entry_price as i128)
+        .checked_mul(numerator.max(0))
+        .ok_or(PerpetualError::Overflow)?
+        / size as i128;
+
+    u64::try_from(price).map_err(|_| PerpetualError::Overflow.into())
 }
 
 /// Calculate unrealized PnL
@@ -77,42 +82,830 @@ pub fn calculate_unrealized_pnl(
     Ok(pnl)
 }
 
-/// Check if position should be liquidated
-pub fn should_liquidate(
+/// Margin health of a position at a given price
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionHealth {
+    /// Collateral plus unrealized PnL less accrued funding and the fee to close
+    pub equity: i64,
+    /// Equity the position needs to open, grow or withdraw margin
+    pub initial_margin: u64,
+    /// Equity the position has to keep to avoid liquidation
+    pub maintenance_margin: u64,
+    /// Equity over the maintenance margin in basis points
+    pub margin_ratio: u64,
+    pub liquidation_price: u64,
+    pub is_liquidatable: bool,
+}
+
+/// Health of a position at `current_price` with `funding_payment` accrued
+/// since its last funding snapshot
+pub fn calculate_position_health(
+    entry_price: u64,
     current_price: u64,
-    liquidation_price: u64,
+    size: u64,
+    side: PositionSide,
+    collateral: u64,
+    funding_payment: i64,
+    params: &MarketParams,
+) -> Result<PositionHealth> {
+    let pnl = calculate_unrealized_pnl(entry_price, current_price, size, side)?;
+    let closing_fee = calculate_fee(size, params.trading_fee as u64)?;
+    let equity = (collateral as i128) + (pnl as i128) - (funding_payment as i128) - (closing_fee as i128);
+    let initial_margin = calculate_fee(size, params.initial_margin_rate as u64)?;
+    let maintenance_margin = calculate_fee(size, params.maintenance_margin_rate as u64)?;
+
+    let margin_ratio = if equity <= 0 {
+        0
+    } else if maintenance_margin == 0 {
+        u64::MAX
+    } else {
+        u64::try_from(equity.saturating_mul(10000) / maintenance_margin as i128).unwrap_or(u64::MAX)
+    };
+
+    Ok(PositionHealth {
+        equity: i64::try_from(equity).map_err(|_| PerpetualError::Overflow)?,
+        initial_margin,
+        maintenance_margin,
+        margin_ratio,
+        liquidation_price: calculate_liquidation_price(
+            entry_price,
+            size,
+            side,
+            collateral,
+            funding_payment,
+            params,
+        )?,
+        is_liquidatable: equity < maintenance_margin as i128,
+    })
+}
+
+/// Leverage of `size` on `collateral`, rounded up
+pub fn calculate_leverage(size: u64, collateral: u64) -> Result<u8> {
+    require!(collateral > 0, PerpetualError::InsufficientCollateral);
+
+    let leverage = (size as u128).div_ceil(collateral as u128);
+    u8::try_from(leverage).map_err(|_| PerpetualError::InvalidLeverage.into())
+}
+
+/// Leverage left after withdrawing margin down to `collateral`
+///
+/// The collateral alone has to keep the position within `max_leverage`, so
+/// unrealized profit never backs a withdrawal past it.
+pub fn calculate_withdrawal_leverage(size: u64, collateral: u64, params: &MarketParams) -> Result<u8> {
+    let min_collateral = (size as u128).div_ceil(params.max_leverage.max(1) as u128);
+    require!(
+        collateral > 0 && collateral as u128 >= min_collateral,
+        PerpetualError::InsufficientCollateral
+    );
+    calculate_leverage(size, collateral)
+}
+
+/// Entry price of a position after adding `added_size` at `price`,
+/// weighted by size
+pub fn calculate_average_entry_price(
+    entry_price: u64,
+    size: u64,
+    price: u64,
+    added_size: u64,
+) -> Result<u64> {
+    let total_size = (size as u128)
+        .checked_add(added_size as u128)
+        .ok_or(PerpetualError::Overflow)?;
+    require!(total_size > 0, PerpetualError::InvalidPositionSize);
+
+    let weighted = (entry_price as u128)
+        .checked_mul(size as u128)
+        .and_then(|value| value.checked_add((price as u128).checked_mul(added_size as u128)?))
+        .ok_or(PerpetualError::Overflow)?;
+
+    u64::try_from(weighted / total_size).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Take-profit and stop-loss must be non-zero and on opposite sides of each
+/// other: a long takes profit above its stop, a short below it
+pub fn validate_trigger_prices(
+    side: PositionSide,
+    take_profit: Option<u64>,
+    stop_loss: Option<u64>,
+) -> Result<()> {
+    require!(
+        take_profit != Some(0) && stop_loss != Some(0),
+        PerpetualError::InvalidTriggerPrice
+    );
+
+    if let (Some(take_profit), Some(stop_loss)) = (take_profit, stop_loss) {
+        let ordered = match side {
+            PositionSide::Long => take_profit > stop_loss,
+            PositionSide::Short => take_profit < stop_loss,
+        };
+        require!(ordered, PerpetualError::InvalidTriggerPrice);
+    }
+
+    Ok(())
+}
+
+/// Level crossed by `price`, if any
+pub fn check_trigger(
     side: PositionSide,
-) -> bool {
-    match side {
-        PositionSide::Long => current_price <= liquidation_price,
-        PositionSide::Short => current_price >= liquidation_price,
+    price: u64,
+    take_profit: Option<u64>,
+    stop_loss: Option<u64>,
+) -> Option<TriggerType> {
+    let (stop_hit, profit_hit) = match side {
+        PositionSide::Long => (
+            stop_loss.is_some_and(|level| price <= level),
+            take_profit.is_some_and(|level| price >= level),
+        ),
+        PositionSide::Short => (
+            stop_loss.is_some_and(|level| price >= level),
+            take_profit.is_some_and(|level| price <= level),
+        ),
+    };
+
+    if stop_hit {
+        Some(TriggerType::StopLoss)
+    } else if profit_hit {
+        Some(TriggerType::TakeProfit)
+    } else {
+        None
     }
 }
 
+/// Fee of `fee_rate` basis points on `size`
+pub fn calculate_fee(size: u64, fee_rate: u64) -> Result<u64> {
+    let fee = (size as u128)
+        .checked_mul(fee_rate as u128)
+        .ok_or(PerpetualError::Overflow)?
+        .checked_div(10000)
+        .ok_or(PerpetualError::Overflow)?;
+
+    u64::try_from(fee).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Funding owed by a position since it snapshotted `entry_index`
+///
+/// Cumulative funding is what longs pay per unit of notional, so a positive
+/// result is paid by the position and a negative result is received.
+pub fn calculate_funding_payment(
+    size: u64,
+    side: PositionSide,
+    entry_index: i64,
+    current_index: i64,
+) -> Result<i64> {
+    let index_delta = (current_index as i128) - (entry_index as i128);
+    let payment = (size as i128)
+        .checked_mul(index_delta)
+        .ok_or(PerpetualError::Overflow)?
+        / FUNDING_PRECISION;
+
+    let payment = match side {
+        PositionSide::Long => payment,
+        PositionSide::Short => -payment,
+    };
+
+    i64::try_from(payment).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Deduct a funding payment from collateral
+///
+/// Returns the new collateral and any part of the payment it could not cover.
+pub fn apply_funding(collateral: u64, funding_payment: i64) -> Result<(u64, u64)> {
+    let remaining = (collateral as i128) - (funding_payment as i128);
+    if remaining >= 0 {
+        Ok((u64::try_from(remaining).map_err(|_| PerpetualError::Overflow)?, 0))
+    } else {
+        Ok((0, u64::try_from(-remaining).map_err(|_| PerpetualError::Overflow)?))
+    }
+}
+
+/// Mark price implied by the open interest skew
+///
+/// A fully long market marks `MAX_MARK_PREMIUM` above the index and a fully
+/// short one the same amount below it; a balanced market marks at the index.
+pub fn calculate_mark_price(index_price: u64, open_interest_long: u64, open_interest_short: u64) -> Result<u64> {
+    let total = (open_interest_long as i128) + (open_interest_short as i128);
+    if total == 0 {
+        return Ok(index_price);
+    }
+
+    let skew = (open_interest_long as i128) - (open_interest_short as i128);
+    let premium = (index_price as i128)
+        .checked_mul(skew)
+        .and_then(|value| value.checked_mul(MAX_MARK_PREMIUM as i128))
+        .ok_or(PerpetualError::Overflow)?
+        / (total * 10000);
+
+    u64::try_from((index_price as i128) + premium).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Premium of the mark over the index as a fraction scaled by `FUNDING_PRECISION`
+pub fn calculate_premium(mark_price: u64, index_price: u64) -> Result<i64> {
+    require!(index_price > 0, PerpetualError::InvalidPrice);
+
+    let premium = ((mark_price as i128) - (index_price as i128))
+        .checked_mul(FUNDING_PRECISION)
+        .ok_or(PerpetualError::Overflow)?
+        / index_price as i128;
+
+    i64::try_from(premium).map_err(|_| PerpetualError::Overflow.into())
+}
+
+/// Sample the premium into the funding TWAP and pay funding once the
+/// funding interval has elapsed
+///
+/// The previous premium is weighted by the time it was in effect. When an
+/// interval closes, its time-weighted premium is paid pro rata of
+/// `FUNDING_PERIOD` into `cumulative_funding` and the funding paid is returned.
+pub fn accrue_funding(funding_rate: &mut FundingRate, premium: i64, now: i64) -> Result<Option<i64>> {
+    let elapsed = now.saturating_sub(funding_rate.last_update).max(0);
+    funding_rate.premium_sum = (funding_rate.last_premium as i128)
+        .checked_mul(elapsed as i128)
+        .and_then(|weighted| funding_rate.premium_sum.checked_add(weighted))
+        .ok_or(PerpetualError::Overflow)?;
+    funding_rate.last_premium = premium;
+    funding_rate.last_update = now;
+
+    let interval = now.saturating_sub(funding_rate.interval_start);
+    if interval < FUNDING_INTERVAL {
+        return Ok(None);
+    }
+
+    let twap_premium = funding_rate.premium_sum / interval as i128;
+    let funding = i64::try_from(funding_rate.premium_sum / FUNDING_PERIOD as i128)
+        .map_err(|_| PerpetualError::Overflow)?;
+
+    funding_rate.cumulative_funding = funding_rate.cumulative_funding
+        .checked_add(funding)
+        .ok_or(PerpetualError::Overflow)?;
+    funding_rate.current_rate = i64::try_from(twap_premium * FUNDING_INTERVAL as i128 / FUNDING_PERIOD as i128)
+        .map_err(|_| PerpetualError::Overflow)?;
+    funding_rate.premium_sum = 0;
+    funding_rate.interval_start = now;
+
+    Ok(Some(funding))
+}
+
+/// Sample the market's current premium into its funding rate
+///
+/// Called by the funding crank and whenever open interest changes, after the
+/// change, so the TWAP weights each skew by how long it lasted.
+pub fn refresh_funding(
+    funding_rate: &mut FundingRate,
+    market: &PerpMarket,
+    index_price: u64,
+    now: i64,
+) -> Result<Option<i64>> {
+    let mark_price = calculate_mark_price(
+        index_price,
+        market.open_interest_long,
+        market.open_interest_short,
+    )?;
+    let premium = calculate_premium(mark_price, index_price)?;
+
+    funding_rate.last_mark_price = mark_price;
+    funding_rate.last_index_price = index_price;
+    accrue_funding(funding_rate, premium, now)
+}
+
+/// Charge `amount` of uncovered bad debt to the open `side` positions, pro
+/// rata of their size
+///
+/// Raises the side's socialized loss by the loss per unit of notional, rounded
+/// up, and returns the increase. Each position pays its share out of its
+/// collateral at its next settlement, the same way as funding.
+pub fn socialize_loss(
+    funding_rate: &mut FundingRate,
+    side: PositionSide,
+    amount: u64,
+    open_interest: u64,
+) -> Result<i64> {
+    require!(open_interest > 0, PerpetualError::InvalidAdlTarget);
+
+    let loss_per_unit = (amount as i128)
+        .checked_mul(FUNDING_PRECISION)
+        .ok_or(PerpetualError::Overflow)?
+        .checked_add(open_interest as i128 - 1)
+        .ok_or(PerpetualError::Overflow)?
+        / open_interest as i128;
+    let loss_per_unit = i64::try_from(loss_per_unit).map_err(|_| PerpetualError::Overflow)?;
+
+    let socialized_loss = match side {
+        PositionSide::Long => &mut funding_rate.socialized_loss_long,
+        PositionSide::Short => &mut funding_rate.socialized_loss_short,
+    };
+    *socialized_loss = socialized_loss
+        .checked_add(loss_per_unit)
+        .ok_or(PerpetualError::Overflow)?;
+
+    Ok(loss_per_unit)
+}
+
+/// Outcome of settling a position against its collateral
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settlement {
+    /// PnL net of funding and the fee actually collected
+    pub realized_pnl: i64,
+    /// Collateral plus realized PnL returned to the owner
+    pub payout: u64,
+    /// Part of the fee covered by the remaining collateral
+    pub fee_paid: u64,
+    /// Loss beyond the collateral that the market has to absorb
+    pub bad_debt: u64,
+}
+
+/// Settle `pnl`, accrued `funding_payment` and `fee` against `collateral`
+///
+/// The fee is taken last and only out of what is left, so an underwater
+/// position's bad debt is its trading loss alone.
+pub fn calculate_settlement(
+    collateral: u64,
+    pnl: i64,
+    funding_payment: i64,
+    fee: u64,
+) -> Result<Settlement> {
+    let equity = (collateral as i128) + (pnl as i128) - (funding_payment as i128);
+
+    let (remaining, bad_debt) = if equity >= 0 {
+        (u64::try_from(equity).map_err(|_| PerpetualError::Overflow)?, 0)
+    } else {
+        (0, u64::try_from(-equity).map_err(|_| PerpetualError::Overflow)?)
+    };
+    let fee_paid = fee.min(remaining);
+    let realized_pnl = (pnl as i128) - (funding_payment as i128) - (fee_paid as i128);
+
+    Ok(Settlement {
+        realized_pnl: i64::try_from(realized_pnl).map_err(|_| PerpetualError::Overflow)?,
+        payout: remaining - fee_paid,
+        fee_paid,
+        bad_debt,
+    })
+}
+
+/// Close `closed_size` of a position with `pnl` on its full size and
+/// funding already settled into `collateral`
+///
+/// The closed part takes its share of the collateral and PnL and pays the
+/// trading fee on its size. Returns the collateral released and its settlement.
+pub fn calculate_partial_close(
+    size: u64,
+    collateral: u64,
+    pnl: i64,
+    closed_size: u64,
+    params: &MarketParams,
+) -> Result<(u64, Settlement)> {
+    require!(
+        closed_size > 0 && closed_size <= size,
+        PerpetualError::InvalidPositionSize
+    );
+
+    let released_collateral = (collateral as u128)
+        .checked_mul(closed_size as u128)
+        .ok_or(PerpetualError::Overflow)?
+        / size as u128;
+    let closed_pnl = (pnl as i128)
+        .checked_mul(closed_size as i128)
+        .ok_or(PerpetualError::Overflow)?
+        / size as i128;
+    let fee = calculate_fee(closed_size, params.trading_fee as u64)?;
+
+    let released_collateral = released_collateral as u64;
+    let settlement = calculate_settlement(
+        released_collateral,
+        i64::try_from(closed_pnl).map_err(|_| PerpetualError::Overflow)?,
+        0,
+        fee,
+    )?;
+
+    Ok((released_collateral, settlement))
+}
+
+/// Size to close so that the rest of the position is back at maintenance
+/// margin plus `PARTIAL_LIQUIDATION_BUFFER` after paying the liquidation fee
+///
+/// Returns 0 when the position already meets that margin and the full size
+/// when it is too small for partial liquidation or cannot be restored.
+pub fn calculate_partial_liquidation_size(size: u64, equity: i128, params: &MarketParams) -> Result<u64> {
+    if size < PARTIAL_LIQUIDATION_MIN_SIZE {
+        return Ok(size);
+    }
+
+    let target_rate = (params.maintenance_margin_rate as u64 + PARTIAL_LIQUIDATION_BUFFER) as i128;
+    let required = (size as i128)
+        .checked_mul(target_rate)
+        .ok_or(PerpetualError::Overflow)?
+        / 10000;
+    if equity >= required {
+        return Ok(0);
+    }
+
+    // equity - fee(closed) >= target * (size - closed)
+    //   => closed >= (target * size - equity) / (target - fee)
+    let deficit = (size as i128)
+        .checked_mul(target_rate)
+        .and_then(|value| value.checked_sub(equity.checked_mul(10000)?))
+        .ok_or(PerpetualError::Overflow)?;
+    let divisor = target_rate - params.liquidation_fee as i128;
+    let closed_size = (deficit + divisor - 1) / divisor;
+
+    Ok(closed_size.min(size as i128) as u64)
+}
+
+/// Outcome of liquidating all or part of a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Liquidation {
+    pub closed_size: u64,
+    /// PnL of the closed size net of the liquidation fee
+    pub realized_pnl: i64,
+    /// Fee paid to the liquidator
+    pub liquidator_fee: u64,
+    /// Collateral left on the position, or returned to the owner on a full liquidation
+    pub remaining_collateral: u64,
+    /// Loss beyond the collateral that the market has to absorb
+    pub bad_debt: u64,
+}
+
+/// Liquidate a position with `pnl` at the current price and funding already
+/// settled into `collateral`
+pub fn calculate_liquidation(
+    size: u64,
+    collateral: u64,
+    pnl: i64,
+    params: &MarketParams,
+) -> Result<Liquidation> {
+    let equity = (collateral as i128) + (pnl as i128);
+    let closed_size = calculate_partial_liquidation_size(size, equity, params)?;
+    require!(closed_size > 0, PerpetualError::NotLiquidatable);
+
+    let closed_pnl = (pnl as i128)
+        .checked_mul(closed_size as i128)
+        .ok_or(PerpetualError::Overflow)?
+        / size as i128;
+    let fee = calculate_fee(closed_size, params.liquidation_fee as u64)?;
+    let settlement = calculate_settlement(
+        collateral,
+        i64::try_from(closed_pnl).map_err(|_| PerpetualError::Overflow)?,
+        0,
+        fee,
+    )?;
+
+    Ok(Liquidation {
+        closed_size,
+        realized_pnl: settlement.realized_pnl,
+        liquidator_fee: settlement.fee_paid,
+        remaining_collateral: settlement.payout,
+        bad_debt: settlement.bad_debt,
+    })
+}
+
+/// Price at which a position's equity (`collateral` net of funding) is zero
+pub fn calculate_bankruptcy_price(
+    entry_price: u64,
+    size: u64,
+    collateral: u64,
+    side: PositionSide,
+) -> Result<u64> {
+    require!(size > 0, PerpetualError::InvalidPositionSize);
+
+    let move_amount = (entry_price as u128)
+        .checked_mul(collateral as u128)
+        .ok_or(PerpetualError::Overflow)?
+        / size as u128;
+
+    let price = match side {
+        PositionSide::Long => (entry_price as u128).saturating_sub(move_amount),
+        PositionSide::Short => (entry_price as u128)
+            .checked_add(move_amount)
+            .ok_or(PerpetualError::Overflow)?,
+    };
+
+    u64::try_from(price).map_err(|_| PerpetualError::Overflow.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::oracle::tests::MOCK_PARAMS;
 
     #[test]
     fn test_calculate_required_margin() {
         let size = 10000u64;
         let leverage = 5u8;
-        let result = calculate_required_margin(size, leverage);
+        let result = calculate_required_margin(size, leverage, &MOCK_PARAMS);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 2000);
+
+        // Initial margin binds below max leverage; leverage outside the bounds is rejected
+        let params = MarketParams { max_leverage: 4, initial_margin_rate: 2500, ..MOCK_PARAMS };
+        assert_eq!(calculate_required_margin(size, 2, &params).unwrap(), 5000);
+        assert_eq!(calculate_required_margin(size, 4, &params).unwrap(), 2500);
+        assert!(calculate_required_margin(size, 5, &params).is_err());
     }
 
     #[test]
     fn test_calculate_liquidation_price() {
-        let entry_price = 10000u64;
-        let leverage = 5u8;
-        
-        let long_liq = calculate_liquidation_price(entry_price, leverage, PositionSide::Long);
-        assert!(long_liq.is_ok());
-        assert!(long_liq.unwrap() < entry_price);
-        
-        let short_liq = calculate_liquidation_price(entry_price, leverage, PositionSide::Short);
-        assert!(short_liq.is_ok());
-        assert!(short_liq.unwrap() > entry_price);
+        // 10% maintenance of 10000 is 1000 and closing costs 10; 1990 of
+        // collateral can lose 980 before hitting it
+        let long_liq = calculate_liquidation_price(10000, 10000, PositionSide::Long, 2000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(long_liq, 9010);
+
+        let short_liq = calculate_liquidation_price(10000, 10000, PositionSide::Short, 2000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(short_liq, 10990);
+
+        // More margin moves the price away, owed funding brings it closer
+        let topped_up = calculate_liquidation_price(10000, 10000, PositionSide::Long, 3000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(topped_up, 8010);
+        let owing = calculate_liquidation_price(10000, 10000, PositionSide::Long, 2000, 500, &MOCK_PARAMS).unwrap();
+        assert_eq!(owing, 9510);
+
+        // A long that cannot fall to maintenance margin never liquidates by price
+        assert_eq!(calculate_liquidation_price(10000, 10000, PositionSide::Long, 20000, 0, &MOCK_PARAMS).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_calculate_position_health() {
+        // Equity 2000 - 10 fee against 1000 maintenance
+        let health = calculate_position_health(10000, 10000, 10000, PositionSide::Long, 2000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(health.equity, 1990);
+        assert_eq!(health.initial_margin, 2000);
+        assert_eq!(health.maintenance_margin, 1000);
+        assert_eq!(health.margin_ratio, 19900);
+        assert_eq!(health.liquidation_price, 9010);
+        assert!(!health.is_liquidatable);
+
+        // Just below the liquidation price
+        let health = calculate_position_health(10000, 9000, 10000, PositionSide::Long, 2000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(health.equity, 990);
+        assert!(health.is_liquidatable);
+
+        // Added margin keeps the same price healthy
+        let health = calculate_position_health(10000, 9000, 10000, PositionSide::Long, 2500, 0, &MOCK_PARAMS).unwrap();
+        assert!(!health.is_liquidatable);
+
+        // Unrealized profit counts toward the initial margin
+        let health = calculate_position_health(10000, 10500, 10000, PositionSide::Long, 1600, 0, &MOCK_PARAMS).unwrap();
+        assert!(health.equity >= health.initial_margin as i64);
+
+        // Accrued funding alone can make it liquidatable
+        let health = calculate_position_health(10000, 10000, 10000, PositionSide::Long, 2000, 1000, &MOCK_PARAMS).unwrap();
+        assert_eq!(health.equity, 990);
+        assert!(health.is_liquidatable);
+
+        // Underwater positions report no margin
+        let health = calculate_position_health(10000, 12000, 10000, PositionSide::Short, 1000, 0, &MOCK_PARAMS).unwrap();
+        assert_eq!(health.equity, -1010);
+        assert_eq!(health.margin_ratio, 0);
+        assert!(health.is_liquidatable);
+    }
+
+    #[test]
+    fn test_calculate_leverage() {
+        assert_eq!(calculate_leverage(10000, 2000).unwrap(), 5);
+        assert_eq!(calculate_leverage(10001, 2000).unwrap(), 6);
+        assert!(calculate_leverage(10000, 0).is_err());
+        assert!(calculate_leverage(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_calculate_withdrawal_leverage() {
+        // 5x max: 10000 of size needs 2000 of collateral
+        assert_eq!(calculate_withdrawal_leverage(10000, 2000, &MOCK_PARAMS).unwrap(), 5);
+        assert_eq!(calculate_withdrawal_leverage(10000, 5000, &MOCK_PARAMS).unwrap(), 2);
+        assert!(calculate_withdrawal_leverage(10000, 1999, &MOCK_PARAMS).is_err());
+        assert_eq!(calculate_withdrawal_leverage(10001, 2001, &MOCK_PARAMS).unwrap(), 5);
+        assert!(calculate_withdrawal_leverage(10001, 2000, &MOCK_PARAMS).is_err());
+        // Beyond what a u8 leverage holds is rejected as undercollateralized
+        assert!(calculate_withdrawal_leverage(1_000_000, 1, &MOCK_PARAMS).is_err());
+        assert!(calculate_withdrawal_leverage(10000, 0, &MOCK_PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_calculate_average_entry_price() {
+        // 10000 at 100 plus 30000 at 120
+        assert_eq!(calculate_average_entry_price(100, 10000, 120, 30000).unwrap(), 115);
+        assert_eq!(calculate_average_entry_price(100, 10000, 120, 0).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_calculate_partial_close() {
+        // Closing a quarter releases a quarter of the collateral and PnL, less
+        // the 0.1% fee on the closed size
+        let (released, settlement) = calculate_partial_close(10000, 2000, 800, 2500, &MOCK_PARAMS).unwrap();
+        assert_eq!(released, 500);
+        assert_eq!(settlement.realized_pnl, 198);
+        assert_eq!(settlement.fee_paid, 2);
+        assert_eq!(settlement.payout, 698);
+        assert_eq!(settlement.bad_debt, 0);
+
+        // A losing close takes its share of the loss
+        let (released, settlement) = calculate_partial_close(10000, 2000, -800, 5000, &MOCK_PARAMS).unwrap();
+        assert_eq!(released, 1000);
+        assert_eq!(settlement.payout, 595);
+
+        assert!(calculate_partial_close(10000, 2000, 0, 0, &MOCK_PARAMS).is_err());
+        assert!(calculate_partial_close(10000, 2000, 0, 10001, &MOCK_PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_validate_trigger_prices() {
+        assert!(validate_trigger_prices(PositionSide::Long, Some(110), Some(90)).is_ok());
+        assert!(validate_trigger_prices(PositionSide::Short, Some(90), Some(110)).is_ok());
+        assert!(validate_trigger_prices(PositionSide::Long, None, Some(90)).is_ok());
+        assert!(validate_trigger_prices(PositionSide::Long, None, None).is_ok());
+
+        assert!(validate_trigger_prices(PositionSide::Long, Some(90), Some(110)).is_err());
+        assert!(validate_trigger_prices(PositionSide::Short, Some(110), Some(90)).is_err());
+        assert!(validate_trigger_prices(PositionSide::Long, Some(0), None).is_err());
+    }
+
+    #[test]
+    fn test_check_trigger() {
+        let long = |price| check_trigger(PositionSide::Long, price, Some(110), Some(90));
+        assert_eq!(long(100), None);
+        assert_eq!(long(110), Some(TriggerType::TakeProfit));
+        assert_eq!(long(90), Some(TriggerType::StopLoss));
+
+        let short = |price| check_trigger(PositionSide::Short, price, Some(90), Some(110));
+        assert_eq!(short(100), None);
+        assert_eq!(short(85), Some(TriggerType::TakeProfit));
+        assert_eq!(short(115), Some(TriggerType::StopLoss));
+
+        assert_eq!(check_trigger(PositionSide::Long, 200, None, None), None);
+    }
+
+    #[test]
+    fn test_calculate_funding_payment() {
+        // 0.1% of notional has accrued since the snapshot
+        let long = calculate_funding_payment(10000, PositionSide::Long, 0, 1_000_000).unwrap();
+        let short = calculate_funding_payment(10000, PositionSide::Short, 0, 1_000_000).unwrap();
+        assert_eq!(long, 10);
+        assert_eq!(short, -10);
+
+        // Negative funding pays longs
+        assert_eq!(calculate_funding_payment(10000, PositionSide::Long, 500_000, -500_000).unwrap(), -10);
+    }
+
+    #[test]
+    fn test_apply_funding() {
+        assert_eq!(apply_funding(2000, 50).unwrap(), (1950, 0));
+        assert_eq!(apply_funding(2000, -50).unwrap(), (2050, 0));
+        assert_eq!(apply_funding(20, 50).unwrap(), (0, 30));
+    }
+
+    #[test]
+    fn test_calculate_mark_price() {
+        let index = 100 * 1_000_000u64;
+        assert_eq!(calculate_mark_price(index, 0, 0).unwrap(), index);
+        assert_eq!(calculate_mark_price(index, 5000, 5000).unwrap(), index);
+
+        // Fully long marks 1% above, 3:1 short marks 0.5% below
+        assert_eq!(calculate_mark_price(index, 5000, 0).unwrap(), 101 * 1_000_000);
+        assert_eq!(calculate_mark_price(index, 2500, 7500).unwrap(), 99_500_000);
+
+        assert_eq!(calculate_premium(101 * 1_000_000, index).unwrap(), 10_000_000);
+        assert_eq!(calculate_premium(99_500_000, index).unwrap(), -5_000_000);
+    }
+
+    fn mock_funding_rate() -> FundingRate {
+        FundingRate {
+            bump: 0,
+            pair: Pubkey::default(),
+            current_rate: 0,
+            last_update: 0,
+            cumulative_funding: 0,
+            last_premium: 0,
+            premium_sum: 0,
+            interval_start: 0,
+            last_mark_price: 0,
+            last_index_price: 0,
+            socialized_loss_long: 0,
+            socialized_loss_short: 0,
+        }
+    }
+
+    #[test]
+    fn test_accrue_funding() {
+        let mut funding_rate = mock_funding_rate();
+
+        // 1% premium for the first half hour, -1% for the second
+        assert_eq!(accrue_funding(&mut funding_rate, 10_000_000, 0).unwrap(), None);
+        assert_eq!(accrue_funding(&mut funding_rate, -10_000_000, 1800).unwrap(), None);
+        assert_eq!(accrue_funding(&mut funding_rate, 10_000_000, 3600).unwrap(), Some(0));
+        assert_eq!(funding_rate.cumulative_funding, 0);
+
+        // A steady 1% premium pays 1/24 of 1% per hour
+        assert_eq!(accrue_funding(&mut funding_rate, 10_000_000, 7200).unwrap(), Some(416_666));
+        assert_eq!(funding_rate.cumulative_funding, 416_666);
+        assert_eq!(funding_rate.current_rate, 416_666);
+        assert_eq!(funding_rate.interval_start, 7200);
+
+        // Longs pay what shorts receive
+        let long = calculate_funding_payment(1_000_000_000, PositionSide::Long, 0, funding_rate.cumulative_funding).unwrap();
+        let short = calculate_funding_payment(1_000_000_000, PositionSide::Short, 0, funding_rate.cumulative_funding).unwrap();
+        assert_eq!(long, 416_666);
+        assert_eq!(short, -416_666);
+    }
+
+    #[test]
+    fn test_socialize_loss() {
+        let mut funding_rate = mock_funding_rate();
+        funding_rate.cumulative_funding = 416_666;
+        let long_entry = funding_rate.funding_index(PositionSide::Long);
+        let short_entry = funding_rate.funding_index(PositionSide::Short);
+
+        // 400 of bad debt over 4000 of long open interest is 10% of notional
+        assert_eq!(socialize_loss(&mut funding_rate, PositionSide::Long, 400, 4000).unwrap(), 100_000_000);
+        let long_index = funding_rate.funding_index(PositionSide::Long);
+        let short_index = funding_rate.funding_index(PositionSide::Short);
+
+        // Every open long pays pro rata of size, without being passed in
+        assert_eq!(calculate_funding_payment(3000, PositionSide::Long, long_entry, long_index).unwrap(), 300);
+        assert_eq!(calculate_funding_payment(1000, PositionSide::Long, long_entry, long_index).unwrap(), 100);
+        // Shorts and longs opened afterwards pay nothing
+        assert_eq!(calculate_funding_payment(1000, PositionSide::Short, short_entry, short_index).unwrap(), 0);
+        assert_eq!(calculate_funding_payment(1000, PositionSide::Long, long_index, long_index).unwrap(), 0);
+
+        // Shorts pay a short-side loss on top of funding
+        socialize_loss(&mut funding_rate, PositionSide::Short, 50, 1000).unwrap();
+        let short_index = funding_rate.funding_index(PositionSide::Short);
+        assert_eq!(calculate_funding_payment(1000, PositionSide::Short, short_entry, short_index).unwrap(), 50);
+
+        // Rounded up so the side repays at least the debt
+        assert_eq!(socialize_loss(&mut funding_rate, PositionSide::Long, 1, 3).unwrap(), 333_333_334);
+        assert!(socialize_loss(&mut funding_rate, PositionSide::Long, 400, 0).is_err());
+    }
+
+    #[test]
+    fn test_calculate_settlement() {
+        // 10% gain on 10000 notional with 2000 collateral, 10 funding and 10 fee
+        let pnl = calculate_unrealized_pnl(10000, 11000, 10000, PositionSide::Long).unwrap();
+        let settlement = calculate_settlement(2000, pnl, 10, 10).unwrap();
+        assert_eq!(settlement, Settlement { realized_pnl: 980, payout: 2980, fee_paid: 10, bad_debt: 0 });
+
+        // 25% loss exceeds the collateral; the shortfall is bad debt and no fee is collected
+        let pnl = calculate_unrealized_pnl(10000, 7500, 10000, PositionSide::Long).unwrap();
+        let settlement = calculate_settlement(2000, pnl, 0, 10).unwrap();
+        assert_eq!(settlement, Settlement { realized_pnl: -2500, payout: 0, fee_paid: 0, bad_debt: 500 });
+
+        // Only the remaining equity can pay the fee
+        let settlement = calculate_settlement(2000, -1995, 0, 10).unwrap();
+        assert_eq!(settlement, Settlement { realized_pnl: -2000, payout: 0, fee_paid: 5, bad_debt: 0 });
+
+        // Received funding adds to the payout
+        let settlement = calculate_settlement(2000, 0, -50, 10).unwrap();
+        assert_eq!(settlement.payout, 2040);
+    }
+
+    #[test]
+    fn test_calculate_partial_liquidation_size() {
+        let size = 100_000_000_000u64;
+
+        // 12% equity already meets maintenance + buffer
+        assert_eq!(calculate_partial_liquidation_size(size, 12_000_000_000, &MOCK_PARAMS).unwrap(), 0);
+
+        // 8% equity: close enough that the rest is back at 12% after the fee
+        let closed = calculate_partial_liquidation_size(size, 8_000_000_000, &MOCK_PARAMS).unwrap();
+        assert_eq!(closed, 40_000_000_000);
+        let remaining_equity = 8_000_000_000 - calculate_fee(closed, MOCK_PARAMS.liquidation_fee as u64).unwrap();
+        assert!(remaining_equity as u128 * 10000 >= (size - closed) as u128 * 1200);
+
+        // Bankrupt and small positions are liquidated in full
+        assert_eq!(calculate_partial_liquidation_size(size, -1, &MOCK_PARAMS).unwrap(), size);
+        assert_eq!(calculate_partial_liquidation_size(10_000, 1, &MOCK_PARAMS).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn test_calculate_liquidation() {
+        // Full: 10000 notional, 2000 collateral, 1900 loss, 2% fee capped at the 100 left
+        let liquidation = calculate_liquidation(10000, 2000, -1900, &MOCK_PARAMS).unwrap();
+        assert_eq!(liquidation, Liquidation {
+            closed_size: 10000,
+            realized_pnl: -2000,
+            liquidator_fee: 100,
+            remaining_collateral: 0,
+            bad_debt: 0,
+        });
+
+        // Full with bad debt: nothing for the liquidator
+        let liquidation = calculate_liquidation(10000, 2000, -2500, &MOCK_PARAMS).unwrap();
+        assert_eq!(liquidation.liquidator_fee, 0);
+        assert_eq!(liquidation.bad_debt, 500);
+
+        // Partial: 8% equity on a large position closes 40% of it
+        let size = 100_000_000_000u64;
+        let liquidation = calculate_liquidation(size, 20_000_000_000, -12_000_000_000, &MOCK_PARAMS).unwrap();
+        assert_eq!(liquidation.closed_size, 40_000_000_000);
+        assert_eq!(liquidation.liquidator_fee, 800_000_000);
+        assert_eq!(liquidation.realized_pnl, -4_800_000_000 - 800_000_000);
+        assert_eq!(liquidation.remaining_collateral, 20_000_000_000 - 4_800_000_000 - 800_000_000);
+
+        assert!(calculate_liquidation(size, 20_000_000_000, 0, &MOCK_PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_calculate_bankruptcy_price() {
+        // 5x long from 100 is bankrupt at 80, short at 120
+        assert_eq!(calculate_bankruptcy_price(100_000_000, 10000, 2000, PositionSide::Long).unwrap(), 80_000_000);
+        assert_eq!(calculate_bankruptcy_price(100_000_000, 10000, 2000, PositionSide::Short).unwrap(), 120_000_000);
+        assert_eq!(calculate_bankruptcy_price(100_000_000, 10000, 20000, PositionSide::Long).unwrap(), 0);
     }
 }
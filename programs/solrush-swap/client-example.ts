@@ -5,6 +5,8 @@
  * - Market Orders
  * - Limit Orders  
  * - DCA Orders
+ * - OCO / Bracket Orders
+ * - Signed Orders (relayer settled)
  */
 
 import * as anchor from "@coral-xyz/anchor";
@@ -15,9 +17,12 @@ import {
   Keypair,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Ed25519Program,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
   createMint,
   createAccount,
   mintTo,
@@ -36,6 +41,19 @@ const OrderSide = {
   Sell: { sell: {} },
 };
 
+const TimeInForce = {
+  GoodTilCancelled: { goodTilCancelled: {} },
+  GoodTilTime: { goodTilTime: {} },
+  ImmediateOrCancel: { immediateOrCancel: {} },
+  FillOrKill: { fillOrKill: {} },
+  PostOnly: { postOnly: {} },
+};
+
+const OcoLeg = {
+  TakeProfit: { takeProfit: {} },
+  StopLoss: { stopLoss: {} },
+};
+
 export class SolRushSwapClient {
   constructor(
     public program: Program<SolrushSwap>,
@@ -125,6 +143,29 @@ export class SolRushSwapClient {
     return { orderBookPda, tx };
   }
 
+  /**
+   * Start tracking the wallet's rolling 30-day volume for fee tiers
+   */
+  async initializeUserVolume() {
+    const user = this.provider.wallet.publicKey;
+    const [userVolumePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_volume"), user.toBuffer()],
+      this.program.programId
+    );
+
+    const tx = await this.program.methods
+      .initializeUserVolume()
+      .accounts({
+        user,
+        userVolume: userVolumePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log("User volume initialized:", tx);
+    return { userVolumePda, tx };
+  }
+
   /**
    * Execute a market order
    * 
@@ -135,69 +176,88 @@ export class SolRushSwapClient {
    *   OrderSide.Buy,
    *   new BN(1_000_000_000), // 1 SOL
    *   new BN(99_000_000),     // Min 99 USDC
-   *   100                      // 1% slippage
+   *   100,                     // 1% slippage
+   *   TimeInForce.FillOrKill
    * );
+   *
+   * Pass `nativeSol = true` to pay (buy) or receive (sell) SOL as lamports
+   * instead of through a wrapped SOL token account.
    */
   async executeMarketOrder(
     tradingPair: any,
     orderSide: any,
     amountIn: BN,
     minimumAmountOut: BN,
-    slippageTolerance: number
+    slippageTolerance: number,
+    timeInForce: any = TimeInForce.FillOrKill,
+    nativeSol: boolean = false
   ) {
     const [poolPda] = this.getPoolPda(tradingPair);
     const pool = await this.program.account.liquidityPool.fetch(poolPda);
-
-    // Get user token accounts (must be created beforehand)
-    const userTokenIn = await this.getUserTokenAccount(
-      orderSide === OrderSide.Buy ? pool.tokenAMint : pool.tokenBMint
-    );
-    const userTokenOut = await this.getUserTokenAccount(
-      orderSide === OrderSide.Buy ? pool.tokenBMint : pool.tokenAMint
-    );
-
-    // Generate PDA for order record
-    const timestamp = Math.floor(Date.now() / 1000);
-    const [orderRecordPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("market_order"),
-        this.provider.wallet.publicKey.toBuffer(),
-        Buffer.from(new BN(timestamp).toArray("le", 8)),
-      ],
+    const user = this.provider.wallet.publicKey;
+    const isBuy = orderSide === OrderSide.Buy;
+
+    // Get user token accounts (must be created beforehand); the SOL side
+    // is skipped when trading native lamports
+    const userTokenIn =
+      nativeSol && isBuy
+        ? null
+        : await this.getUserTokenAccount(isBuy ? pool.tokenAMint : pool.tokenBMint);
+    const userTokenOut =
+      nativeSol && !isBuy
+        ? null
+        : await this.getUserTokenAccount(isBuy ? pool.tokenBMint : pool.tokenAMint);
+    const [nativeSolAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("native_sol"), user.toBuffer()],
       this.program.programId
     );
 
     const tx = await this.program.methods
-      .executeMarketOrder(amountIn, minimumAmountOut, slippageTolerance, orderSide)
+      .executeMarketOrder(
+        amountIn,
+        minimumAmountOut,
+        slippageTolerance,
+        orderSide,
+        timeInForce
+      )
       .accounts({
-        user: this.provider.wallet.publicKey,
+        user,
         pool: poolPda,
         userTokenIn,
         userTokenOut,
         poolTokenAVault: pool.tokenAVault,
         poolTokenBVault: pool.tokenBVault,
-        orderRecord: orderRecordPda,
+        tradeHistory: null,
+        // Pass the admin fee schedule, the user's volume PDA and RUSH stake
+        // account to trade at a discounted tier fee
+        feeSchedule: null,
+        userVolume: null,
+        rushStake: null,
+        nativeSolAccount: nativeSol ? nativeSolAccount : null,
+        nativeMint: nativeSol ? NATIVE_MINT : null,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
 
+    // Fill details are emitted as a MarketOrderExecuted event
     console.log("Market order executed:", tx);
-    return { tx, orderRecordPda };
+    return tx;
   }
 
   /**
    * Place a limit order
    * 
    * @example
-   * // Buy USDC when price reaches 150 or lower
+   * // Buy USDC with SOL once 1 SOL fetches 150 USDC or more
    * await client.placeLimitOrder(
    *   TradingPair.SolUsdc,
    *   OrderSide.Buy,
    *   new BN(1_000_000_000),   // 1 SOL
    *   new BN(150_000_000_000), // 150 USDC/SOL
    *   100,                      // 1% slippage
-   *   0                         // Never expires
+   *   0,                        // Never expires
+   *   TimeInForce.GoodTilCancelled
    * );
    */
   async placeLimitOrder(
@@ -206,7 +266,10 @@ export class SolRushSwapClient {
     amountIn: BN,
     limitPrice: BN,
     slippageTolerance: number,
-    expiresAt: number = 0
+    expiresAt: number = 0,
+    timeInForce: any = expiresAt > 0
+      ? TimeInForce.GoodTilTime
+      : TimeInForce.GoodTilCancelled
   ) {
     const [poolPda] = this.getPoolPda(tradingPair);
     const [orderBookPda] = this.getOrderBookPda(tradingPair);
@@ -239,7 +302,8 @@ export class SolRushSwapClient {
         amountIn,
         limitPrice,
         slippageTolerance,
-        new BN(expiresAt)
+        new BN(expiresAt),
+        timeInForce
       )
       .accounts({
         user: this.provider.wallet.publicKey,
@@ -274,7 +338,10 @@ export class SolRushSwapClient {
         user: this.provider.wallet.publicKey,
         orderBook: orderBookPda,
         limitOrder: limitOrderPda,
-        userTokenAccount: limitOrder.userTokenAccount,
+        // Orders funded with native SOL are refunded as lamports
+        userTokenAccount: limitOrder.userTokenAccount.equals(PublicKey.default)
+          ? null
+          : limitOrder.userTokenAccount,
         escrowTokenAccount: limitOrder.escrowTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -307,6 +374,9 @@ export class SolRushSwapClient {
         escrowTokenAccount: limitOrder.escrowTokenAccount,
         poolTokenAVault: pool.tokenAVault,
         poolTokenBVault: pool.tokenBVault,
+        feeSchedule: null,
+        userVolume: null,
+        rushStake: null,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -321,6 +391,7 @@ export class SolRushSwapClient {
    * @example
    * // DCA: Buy $100 USDC every day for 30 days
    * await client.createDCAOrder(
+   *   new BN(1),                 // Order id (unique per wallet)
    *   TradingPair.SolUsdc,
    *   OrderSide.Buy,
    *   new BN(100_000_000),      // 100 USDC per cycle
@@ -332,6 +403,7 @@ export class SolRushSwapClient {
    * );
    */
   async createDCAOrder(
+    orderId: BN,
     tradingPair: any,
     orderSide: any,
     amountPerCycle: BN,
@@ -344,12 +416,11 @@ export class SolRushSwapClient {
     const [poolPda] = this.getPoolPda(tradingPair);
     const pool = await this.program.account.liquidityPool.fetch(poolPda);
 
-    const timestamp = Math.floor(Date.now() / 1000);
     const [dcaOrderPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("dca_order"),
         this.provider.wallet.publicKey.toBuffer(),
-        Buffer.from(new BN(timestamp).toArray("le", 8)),
+        Buffer.from(orderId.toArray("le", 8)),
       ],
       this.program.programId
     );
@@ -362,6 +433,7 @@ export class SolRushSwapClient {
 
     const tx = await this.program.methods
       .createDcaOrder(
+        orderId,
         tradingPair,
         orderSide,
         amountPerCycle,
@@ -407,6 +479,9 @@ export class SolRushSwapClient {
         escrowTokenAccount: dcaOrder.escrowTokenAccount,
         poolTokenAVault: pool.tokenAVault,
         poolTokenBVault: pool.tokenBVault,
+        feeSchedule: null,
+        userVolume: null,
+        rushStake: null,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -439,13 +514,140 @@ export class SolRushSwapClient {
   /**
    * Get current market price from pool
    */
+  /**
+   * Enter at market with a take-profit / stop-loss exit
+   *
+   * @example
+   * // Buy USDC with 1 SOL, exit back to SOL at 140 (take profit) or 160 (stop)
+   * await client.placeBracketOrder(
+   *   TradingPair.SolUsdc,
+   *   new BN(1),
+   *   OrderSide.Buy,
+   *   new BN(1_000_000_000),   // 1 SOL
+   *   new BN(145_000_000),     // Min 145 USDC
+   *   new BN(140_000_000_000), // Take profit (USDC per SOL, scaled by 1e9)
+   *   new BN(160_000_000_000), // Stop trigger
+   *   new BN(0),               // Stop-market
+   *   100                      // 1% stop slippage
+   * );
+   */
+  async placeBracketOrder(
+    tradingPair: any,
+    orderId: BN,
+    entrySide: any,
+    amountIn: BN,
+    minimumAmountOut: BN,
+    takeProfitPrice: BN,
+    stopTriggerPrice: BN,
+    stopLimitPrice: BN,
+    slippageTolerance: number,
+    expiresAt: number = 0
+  ) {
+    const [poolPda] = this.getPoolPda(tradingPair);
+    const pool = await this.program.account.liquidityPool.fetch(poolPda);
+    const user = this.provider.wallet.publicKey;
+    const isBuy = entrySide === OrderSide.Buy;
+
+    const [ocoOrderPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("oco_order"),
+        user.toBuffer(),
+        poolPda.toBuffer(),
+        Buffer.from(orderId.toArray("le", 8)),
+      ],
+      this.program.programId
+    );
+    const [userOrdersPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_orders"), user.toBuffer()],
+      this.program.programId
+    );
+
+    const exitMint = isBuy ? pool.tokenBMint : pool.tokenAMint;
+    const escrowTokenAccount = Keypair.generate();
+
+    const tx = await this.program.methods
+      .placeBracketOrder(
+        orderId,
+        entrySide,
+        amountIn,
+        minimumAmountOut,
+        takeProfitPrice,
+        stopTriggerPrice,
+        stopLimitPrice,
+        slippageTolerance,
+        new BN(expiresAt)
+      )
+      .accounts({
+        user,
+        pool: poolPda,
+        ocoOrder: ocoOrderPda,
+        userTokenIn: await this.getUserTokenAccount(
+          isBuy ? pool.tokenAMint : pool.tokenBMint
+        ),
+        userTokenAccount: await this.getUserTokenAccount(exitMint),
+        tokenMint: exitMint,
+        escrowTokenAccount: escrowTokenAccount.publicKey,
+        poolTokenAVault: pool.tokenAVault,
+        poolTokenBVault: pool.tokenBVault,
+        userOrders: userOrdersPda,
+        tradeHistory: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([escrowTokenAccount])
+      .rpc();
+
+    console.log("Bracket order placed:", tx);
+    return { tx, ocoOrderPda };
+  }
+
+  /**
+   * Aggregated depth between two prices, read through simulateTransaction
+   */
+  async getOrderBookDepth(
+    tradingPair: any,
+    minPrice: BN,
+    maxPrice: BN,
+    numLevels: number
+  ) {
+    const [poolPda] = this.getPoolPda(tradingPair);
+    const [orderBookPda] = this.getOrderBookPda(tradingPair);
+
+    const limitOrders = await this.program.account.limitOrder.all([
+      {
+        memcmp: {
+          offset: 8 + 32, // After discriminator and owner
+          bytes: orderBookPda.toBase58(),
+        },
+      },
+    ]);
+
+    return this.program.methods
+      .getOrderBookDepth(minPrice, maxPrice, numLevels)
+      .accounts({
+        pool: poolPda,
+        orderBook: orderBookPda,
+      })
+      .remainingAccounts(
+        limitOrders.map((order) => ({
+          pubkey: order.publicKey,
+          isSigner: false,
+          isWritable: false,
+        }))
+      )
+      .view();
+  }
+
   async getMarketPrice(tradingPair: any): Promise<number> {
     const [poolPda] = this.getPoolPda(tradingPair);
     const pool = await this.program.account.liquidityPool.fetch(poolPda);
 
-    // Price in token B per token A
+    // Whole token B per whole token A, the same convention as order prices
+    // (which are additionally scaled by 1e9)
     const price =
-      (pool.reserveB.toNumber() * 1e9) / pool.reserveA.toNumber();
+      (pool.reserveB.toNumber() / 10 ** pool.tokenBDecimals) /
+      (pool.reserveA.toNumber() / 10 ** pool.tokenADecimals);
     return price;
   }
 
@@ -487,6 +689,88 @@ export class SolRushSwapClient {
     );
   }
 
+  /**
+   * Bytes an order owner signs for a relayer-settled order
+   *
+   * Sign with the owner's wallet (e.g. `wallet.signMessage(message)`),
+   * after approving the order delegate PDA on the input token account.
+   */
+  buildSignedOrderMessage(order: any): Buffer {
+    return Buffer.concat([
+      Buffer.from("solrush-swap:signed-order:v1"),
+      this.program.coder.types.encode("SignedOrder", order),
+    ]);
+  }
+
+  /**
+   * Settle a signed order as the relayer
+   *
+   * @example
+   * const order = {
+   *   owner, pool: poolPda, orderSide: OrderSide.Buy,
+   *   amountIn: new BN(1_000_000_000), minimumAmountOut: new BN(145_000_000),
+   *   relayerFee: new BN(1_000_000), nonce: new BN(0),
+   *   expiresAt: new BN(Math.floor(Date.now() / 1000) + 300),
+   * };
+   * const signature = await ownerWallet.signMessage(client.buildSignedOrderMessage(order));
+   * await client.fillSignedOrder(TradingPair.SolUsdc, order, signature, relayerFeeAccount);
+   */
+  async fillSignedOrder(
+    tradingPair: any,
+    order: any,
+    signature: Uint8Array,
+    relayerFeeAccount: PublicKey
+  ) {
+    const [poolPda] = this.getPoolPda(tradingPair);
+    const pool = await this.program.account.liquidityPool.fetch(poolPda);
+    const isBuy = order.orderSide === OrderSide.Buy;
+
+    const [signedOrderNoncePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signed_order_nonce"), order.owner.toBuffer()],
+      this.program.programId
+    );
+    const [orderDelegatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_delegate")],
+      this.program.programId
+    );
+
+    // Must be the instruction right before fill_signed_order
+    const verifyIx = Ed25519Program.createInstructionWithPublicKey({
+      publicKey: order.owner.toBytes(),
+      message: this.buildSignedOrderMessage(order),
+      signature,
+    });
+
+    const tx = await this.program.methods
+      .fillSignedOrder(order)
+      .accounts({
+        relayer: this.provider.wallet.publicKey,
+        pool: poolPda,
+        signedOrderNonce: signedOrderNoncePda,
+        orderDelegate: orderDelegatePda,
+        ownerTokenIn: await this.getOwnerTokenAccount(
+          order.owner,
+          isBuy ? pool.tokenAMint : pool.tokenBMint
+        ),
+        ownerTokenOut: await this.getOwnerTokenAccount(
+          order.owner,
+          isBuy ? pool.tokenBMint : pool.tokenAMint
+        ),
+        relayerFeeAccount,
+        poolTokenAVault: pool.tokenAVault,
+        poolTokenBVault: pool.tokenBVault,
+        tradeHistory: null,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .preInstructions([verifyIx])
+      .rpc();
+
+    // Fill details are emitted as a SignedOrderFilled event
+    console.log("Signed order filled:", tx);
+    return tx;
+  }
+
   // Helper methods
   private async getUserTokenAccount(mint: PublicKey): Promise<PublicKey> {
     // Implement token account lookup/creation
@@ -494,6 +778,14 @@ export class SolRushSwapClient {
     throw new Error("Implement getUserTokenAccount");
   }
 
+  private async getOwnerTokenAccount(
+    owner: PublicKey,
+    mint: PublicKey
+  ): Promise<PublicKey> {
+    // Implement token account lookup for another wallet
+    throw new Error("Implement getOwnerTokenAccount");
+  }
+
   private serializeTradingPair(tradingPair: any): Uint8Array {
     // Serialize trading pair enum
     if (tradingPair.solUsdc) return new Uint8Array([0]);
@@ -551,4 +843,4 @@ if (require.main === module) {
   main().catch(console.error);
 }
 
-export { TradingPair, OrderSide };
+export { TradingPair, OrderSide, OcoLeg };
@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use crate::errors::SwapError;
+
+// Layout of Ed25519 program instruction data
+const SIGNATURE_COUNT_OFFSET: usize = 0;
+const OFFSETS_START: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+/// Instruction index meaning "data is in this same instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| SwapError::InvalidEd25519Instruction.into())
+}
+
+fn read_slice(data: &[u8], offset: u16, len: usize) -> Result<&[u8]> {
+    let start = offset as usize;
+    data.get(start..start + len)
+        .ok_or_else(|| SwapError::InvalidEd25519Instruction.into())
+}
+
+/// Extract the signer and message from Ed25519 program instruction data
+///
+/// Only a single signature whose pubkey, signature and message are all
+/// embedded in the instruction itself is accepted.
+pub fn parse_ed25519_instruction_data(data: &[u8]) -> Result<(Pubkey, &[u8])> {
+    require!(
+        data.get(SIGNATURE_COUNT_OFFSET) == Some(&1),
+        SwapError::InvalidEd25519Instruction
+    );
+    require!(data.len() >= OFFSETS_START + OFFSETS_LEN, SwapError::InvalidEd25519Instruction);
+
+    let signature_offset = read_u16(data, OFFSETS_START)?;
+    let signature_instruction_index = read_u16(data, OFFSETS_START + 2)?;
+    let public_key_offset = read_u16(data, OFFSETS_START + 4)?;
+    let public_key_instruction_index = read_u16(data, OFFSETS_START + 6)?;
+    let message_data_offset = read_u16(data, OFFSETS_START + 8)?;
+    let message_data_size = read_u16(data, OFFSETS_START + 10)?;
+    let message_instruction_index = read_u16(data, OFFSETS_START + 12)?;
+
+    require!(
+        signature_instruction_index == CURRENT_INSTRUCTION
+            && public_key_instruction_index == CURRENT_INSTRUCTION
+            && message_instruction_index == CURRENT_INSTRUCTION,
+        SwapError::InvalidEd25519Instruction
+    );
+
+    read_slice(data, signature_offset, SIGNATURE_LEN)?;
+    let public_key = Pubkey::try_from(read_slice(data, public_key_offset, PUBKEY_LEN)?)
+        .map_err(|_| SwapError::InvalidEd25519Instruction)?;
+    let message = read_slice(data, message_data_offset, message_data_size as usize)?;
+
+    Ok((public_key, message))
+}
+
+/// Require that the instruction right before the current one is an Ed25519
+/// program signature check of `message` by `signer`
+///
+/// The Ed25519 program fails the whole transaction if the signature is bad,
+/// so finding the instruction with the expected signer and message is enough.
+pub fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, SwapError::MissingEd25519Instruction);
+
+    let instruction = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    require_keys_eq!(
+        instruction.program_id,
+        ed25519_program::ID,
+        SwapError::MissingEd25519Instruction
+    );
+    require!(instruction.accounts.is_empty(), SwapError::InvalidEd25519Instruction);
+
+    let (public_key, signed_message) = parse_ed25519_instruction_data(&instruction.data)?;
+    require_keys_eq!(public_key, *signer, SwapError::SignatureMismatch);
+    require!(signed_message == message, SwapError::SignatureMismatch);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ed25519_instruction_data(public_key: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset = (OFFSETS_START + OFFSETS_LEN) as u16;
+        let signature_offset = public_key_offset + PUBKEY_LEN as u16;
+        let message_offset = signature_offset + SIGNATURE_LEN as u16;
+
+        let mut data = vec![1u8, 0u8];
+        for value in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(public_key.as_ref());
+        data.extend_from_slice(&[7u8; SIGNATURE_LEN]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_parse_ed25519_instruction_data() {
+        let signer = Pubkey::new_unique();
+        let message = b"signed order";
+
+        let data = ed25519_instruction_data(&signer, message, CURRENT_INSTRUCTION);
+        let (public_key, signed_message) = parse_ed25519_instruction_data(&data).unwrap();
+        assert_eq!(public_key, signer);
+        assert_eq!(signed_message, message);
+
+        // Data referenced from another instruction is rejected
+        let data = ed25519_instruction_data(&signer, message, 0);
+        assert!(parse_ed25519_instruction_data(&data).is_err());
+
+        // Truncated data is rejected
+        let data = ed25519_instruction_data(&signer, message, CURRENT_INSTRUCTION);
+        assert!(parse_ed25519_instruction_data(&data[..data.len() - 1]).is_err());
+
+        // Multiple signatures are rejected
+        let mut data = ed25519_instruction_data(&signer, message, CURRENT_INSTRUCTION);
+        data[0] = 2;
+        assert!(parse_ed25519_instruction_data(&data).is_err());
+    }
+}
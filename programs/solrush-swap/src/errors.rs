@@ -86,4 +86,100 @@ pub enum SwapError {
     
     #[msg("Invalid expiration time")]
     InvalidExpirationTime,
+    
+    #[msg("Invalid trigger price")]
+    InvalidTriggerPrice,
+    
+    #[msg("Stop order trigger price not reached")]
+    StopPriceNotTriggered,
+    
+    #[msg("Order has not expired")]
+    OrderNotExpired,
+    
+    #[msg("User open order limit reached")]
+    UserOrdersFull,
+    
+    #[msg("Time in force not supported for this order")]
+    InvalidTimeInForce,
+    
+    #[msg("Immediate-or-cancel order could not be filled")]
+    ImmediateOrCancelNotFilled,
+    
+    #[msg("Fill-or-kill order could not be completely filled")]
+    FillOrKillNotFilled,
+    
+    #[msg("Good-til-time order requires a future expiration")]
+    GoodTilTimeRequiresExpiry,
+    
+    #[msg("Post-only order would execute immediately")]
+    PostOnlyWouldTake,
+    
+    #[msg("Unauthorized")]
+    Unauthorized,
+    
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    
+    #[msg("Pool price deviates too far from oracle price")]
+    OraclePriceDeviation,
+    
+    #[msg("Trade history does not belong to this pool")]
+    InvalidTradeHistory,
+    
+    #[msg("Missing ed25519 signature verification instruction")]
+    MissingEd25519Instruction,
+    
+    #[msg("Invalid ed25519 signature verification instruction")]
+    InvalidEd25519Instruction,
+    
+    #[msg("Signature does not match the expected signer and message")]
+    SignatureMismatch,
+    
+    #[msg("Signed order nonce already used or cancelled")]
+    InvalidNonce,
+    
+    #[msg("Invalid RFQ quote")]
+    InvalidRfqQuote,
+    
+    #[msg("Pool price has not improved on the trailing stop's best price")]
+    TrailingStopNotImproved,
+    
+    #[msg("Invalid depth price range or level count")]
+    InvalidDepthRange,
+    
+    #[msg("Account is not a limit order of this order book")]
+    InvalidOrderAccount,
+    
+    #[msg("Native SOL is only supported on the wrapped SOL side of the pool")]
+    InvalidNativeSolAccounts,
+    
+    #[msg("Pool account does not use the legacy layout")]
+    PoolAlreadyMigrated,
+    
+    #[msg("Order book account does not use the legacy layout")]
+    OrderBookAlreadyMigrated,
+    
+    #[msg("Next order id is below the order book's order count")]
+    InvalidNextOrderId,
+    
+    #[msg("Oracle publish time is ahead of the clock")]
+    FutureOraclePrice,
+    
+    #[msg("Pool keeps a trade history; pass its account")]
+    MissingTradeHistory,
+    
+    #[msg("Order account passed more than once")]
+    DuplicateOrderAccount,
+    
+    #[msg("Fee account is not owned by the expected program")]
+    InvalidFeeAccount,
+    
+    #[msg("Maker rebate is due; pass the owner's input token account")]
+    MissingRebateAccount,
 }
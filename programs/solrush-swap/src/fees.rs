@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+use solrush_admin::state::FeeSchedule;
+use solrush_token::state::StakeAccount;
+use crate::errors::SwapError;
+use crate::state::{LiquidityPool, OrderSide, UserVolume};
+
+/// Apply a discount in basis points to a fee rate
+fn apply_discount(fee_rate: u16, discount_bps: u16) -> u16 {
+    let discount = fee_rate as u32 * discount_bps.min(10000) as u32 / 10000;
+    fee_rate - discount as u16
+}
+
+/// Pool fee after the volume tier and staking discounts of a schedule
+///
+/// Takes the highest tier whose minimum the rolling volume reaches; the
+/// staking discount stacks on top, capped at the whole fee.
+pub fn calculate_tiered_fee_rate(
+    base_fee_rate: u16,
+    schedule: &FeeSchedule,
+    rolling_volume: u64,
+    staked_amount: u64,
+) -> u16 {
+    let tier_discount = schedule
+        .tiers
+        .iter()
+        .filter(|tier| rolling_volume >= tier.min_volume)
+        .map(|tier| tier.discount_bps)
+        .max()
+        .unwrap_or(0);
+
+    let staking_discount = if staked_amount > 0 && staked_amount >= schedule.min_staked_amount {
+        schedule.staking_discount_bps
+    } else {
+        0
+    };
+
+    apply_discount(base_fee_rate, tier_discount.saturating_add(staking_discount))
+}
+
+/// Part of a limit fill's fee paid back to the resting order's owner
+pub fn calculate_maker_rebate(fee_amount: u64, schedule: Option<&FeeSchedule>) -> u64 {
+    match schedule {
+        Some(schedule) => (fee_amount as u128 * schedule.maker_rebate_bps.min(10000) as u128 / 10000) as u64,
+        None => 0,
+    }
+}
+
+/// Fee rate for a trader, falling back to the pool fee when no schedule is passed
+pub fn resolve_fee_rate(
+    pool: &LiquidityPool,
+    schedule: Option<&FeeSchedule>,
+    user_volume: Option<&UserVolume>,
+    rush_stake: Option<&StakeAccount>,
+    now: i64,
+) -> u16 {
+    match schedule {
+        Some(schedule) => calculate_tiered_fee_rate(
+            pool.fee_rate,
+            schedule,
+            user_volume.map_or(0, |volume| volume.rolling_volume(now)),
+            rush_stake.map_or(0, |stake| stake.active_amount(now)),
+        ),
+        None => pool.fee_rate,
+    }
+}
+
+/// Token A side of a fill, the unit fee tier volume is counted in
+pub fn token_a_volume(order_side: OrderSide, amount_in: u64, amount_out: u64) -> u64 {
+    match order_side {
+        OrderSide::Buy => amount_in,
+        OrderSide::Sell => amount_out,
+    }
+}
+
+/// Add a fill to the trader's rolling volume, if they keep one
+pub fn record_user_volume(user_volume: Option<&mut UserVolume>, now: i64, amount: u64) -> Result<()> {
+    if let Some(user_volume) = user_volume {
+        user_volume.record(now, amount)?;
+    }
+    Ok(())
+}
+
+/// Deserialize an account at a seed-derived address, or `None` if it has not
+/// been created yet
+///
+/// Keeper fills read the order owner's fee accounts this way, so a keeper
+/// cannot leave out accounts that exist to change the owner's fee. The
+/// owner's `UserOrders` index is read the same way.
+pub fn load_if_initialized<T: AccountDeserialize + Owner>(account: &AccountInfo) -> Result<Option<T>> {
+    if account.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*account.owner, T::owner(), SwapError::InvalidFeeAccount);
+    T::try_deserialize(&mut &account.try_borrow_data()?[..]).map(Some)
+}
+
+/// Add a fill to a volume read by `load_if_initialized` and write it back
+pub fn record_user_volume_at(
+    account: &AccountInfo,
+    user_volume: Option<UserVolume>,
+    now: i64,
+    amount: u64,
+) -> Result<()> {
+    if let Some(mut user_volume) = user_volume {
+        user_volume.record(now, amount)?;
+        user_volume.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solrush_admin::state::FeeTier;
+    use crate::state::VOLUME_WINDOW_DAYS;
+
+    const DAY: i64 = 86_400;
+
+    fn schedule() -> FeeSchedule {
+        FeeSchedule {
+            bump: 0,
+            tiers: vec![
+                FeeTier { min_volume: 1_000, discount_bps: 1000 },
+                FeeTier { min_volume: 10_000, discount_bps: 3000 },
+            ],
+            staking_discount_bps: 2000,
+            min_staked_amount: 500,
+            maker_rebate_bps: 5000,
+            last_updated: 0,
+        }
+    }
+
+    #[test]
+    fn test_calculate_tiered_fee_rate() {
+        let schedule = schedule();
+
+        assert_eq!(calculate_tiered_fee_rate(30, &schedule, 0, 0), 30);
+        assert_eq!(calculate_tiered_fee_rate(30, &schedule, 1_000, 0), 27);
+        assert_eq!(calculate_tiered_fee_rate(30, &schedule, 50_000, 0), 21);
+
+        // Staking discount needs the minimum stake and stacks with the tier
+        assert_eq!(calculate_tiered_fee_rate(30, &schedule, 0, 499), 30);
+        assert_eq!(calculate_tiered_fee_rate(30, &schedule, 50_000, 500), 15);
+
+        // Makers get half of the fee they paid back
+        assert_eq!(calculate_maker_rebate(301, Some(&schedule)), 150);
+        assert_eq!(calculate_maker_rebate(301, None), 0);
+    }
+
+    #[test]
+    fn test_user_volume_rolling_window() {
+        let mut volume = UserVolume {
+            owner: Pubkey::default(),
+            daily_volume: [0; VOLUME_WINDOW_DAYS],
+            last_day: 0,
+            bump: 0,
+        };
+
+        let start = 100 * DAY;
+        volume.record(start, 100).unwrap();
+        volume.record(start + DAY / 2, 50).unwrap();
+        volume.record(start + 10 * DAY, 200).unwrap();
+        assert_eq!(volume.rolling_volume(start + 10 * DAY), 350);
+
+        // The first day leaves the window after 30 days
+        assert_eq!(volume.rolling_volume(start + 29 * DAY), 350);
+        assert_eq!(volume.rolling_volume(start + 30 * DAY), 200);
+
+        // Recording after a long gap drops everything older
+        volume.record(start + 100 * DAY, 10).unwrap();
+        assert_eq!(volume.rolling_volume(start + 100 * DAY), 10);
+    }
+
+    #[test]
+    fn test_load_if_initialized() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0u64;
+
+        // An address nobody created yet reads as missing
+        let mut empty = vec![];
+        let system = anchor_lang::system_program::ID;
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut empty, &system, false, 0);
+        assert!(load_if_initialized::<UserVolume>(&account).unwrap().is_none());
+
+        let mut volume = UserVolume {
+            owner: key,
+            daily_volume: [0; VOLUME_WINDOW_DAYS],
+            last_day: 0,
+            bump: 0,
+        };
+        volume.daily_volume[0] = 100;
+        let mut data = Vec::new();
+        volume.try_serialize(&mut data).unwrap();
+
+        let mut lamports = 0u64;
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        let loaded = load_if_initialized::<UserVolume>(&account).unwrap().unwrap();
+        assert_eq!(loaded.owner, key);
+        record_user_volume_at(&account, Some(loaded), 0, 50).unwrap();
+        let stored = load_if_initialized::<UserVolume>(&account).unwrap().unwrap();
+        assert_eq!(stored.daily_volume[0], 150);
+
+        // Data owned by another program is rejected instead of ignored
+        let mut lamports = 0u64;
+        let mut data = account.try_borrow_data().unwrap().to_vec();
+        let other = Pubkey::new_unique();
+        let foreign = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &other, false, 0);
+        assert!(load_if_initialized::<UserVolume>(&foreign).is_err());
+    }
+}
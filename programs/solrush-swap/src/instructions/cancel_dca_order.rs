@@ -10,8 +10,13 @@ pub struct CancelDCAOrder<'info> {
 
     #[account(
         mut,
+        seeds = [
+            b"dca_order",
+            user.key().as_ref(),
+            &dca_order.order_id.to_le_bytes()
+        ],
+        bump = dca_order.bump,
         constraint = dca_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
-        constraint = dca_order.order_status != OrderStatus::Filled @ SwapError::OrderAlreadyFilled,
         constraint = dca_order.order_status != OrderStatus::Cancelled @ SwapError::OrderAlreadyCancelled,
         close = user
     )]
@@ -19,7 +24,8 @@ pub struct CancelDCAOrder<'info> {
 
     #[account(
         mut,
-        constraint = user_token_account.owner == user.key()
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
     )]
     pub user_token_account: Account<'info, TokenAccount>,
 
@@ -30,30 +36,33 @@ pub struct CancelDCAOrder<'info> {
     pub escrow_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
 }
 
 pub fn handler(ctx: Context<CancelDCAOrder>) -> Result<()> {
     let dca_order = &mut ctx.accounts.dca_order;
 
-    // Calculate remaining cycles
-    let remaining_cycles = dca_order.total_cycles
-        .checked_sub(dca_order.cycles_executed)
-        .ok_or(SwapError::MathError)?;
+    // Escrow may hold top-ups beyond the remaining cycles, so refund the balance
+    let remaining_amount = ctx.accounts.escrow_token_account.amount;
 
-    let remaining_amount = (dca_order.amount_per_cycle as u128)
-        .checked_mul(remaining_cycles as u128)
-        .ok_or(SwapError::CalculationOverflow)? as u64;
+    let order_id_bytes = dca_order.order_id.to_le_bytes();
+    let seeds = &[
+        b"dca_order",
+        dca_order.owner.as_ref(),
+        order_id_bytes.as_ref(),
+        &[dca_order.bump],
+    ];
+    let signer = &[&seeds[..]];
 
     if remaining_amount > 0 {
         // Return remaining escrowed tokens to user
-        let seeds = &[
-            b"dca_order",
-            dca_order.owner.as_ref(),
-            &dca_order.last_execution.to_le_bytes(),
-            &[dca_order.bump],
-        ];
-        let signer = &[&seeds[..]];
-
         token::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -66,23 +75,26 @@ pub fn handler(ctx: Context<CancelDCAOrder>) -> Result<()> {
             ),
             remaining_amount,
         )?;
-
-        // Close escrow account
-        token::close_account(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                CloseAccount {
-                    account: ctx.accounts.escrow_token_account.to_account_info(),
-                    destination: ctx.accounts.user.to_account_info(),
-                    authority: dca_order.to_account_info(),
-                },
-                signer,
-            ),
-        )?;
     }
 
+    // Close escrow account
+    token::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: dca_order.to_account_info(),
+            },
+            signer,
+        ),
+    )?;
+
     // Update order status
     dca_order.order_status = OrderStatus::Cancelled;
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.remove_order(&dca_order.key());
+    }
 
     msg!(
         "DCA order cancelled: {}/{} cycles completed, {} tokens returned",
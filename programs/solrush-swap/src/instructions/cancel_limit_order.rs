@@ -21,7 +21,7 @@ pub struct CancelLimitOrder<'info> {
             b"limit_order",
             user.key().as_ref(),
             order_book.key().as_ref(),
-            // Order index would be passed as remaining_accounts
+            &limit_order.order_index.to_le_bytes()
         ],
         bump = limit_order.bump,
         constraint = limit_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
@@ -31,11 +31,12 @@ pub struct CancelLimitOrder<'info> {
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
+    /// Omitted to receive a wrapped SOL escrow back as native lamports
     #[account(
         mut,
         constraint = user_token_account.owner == user.key()
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -44,6 +45,14 @@ pub struct CancelLimitOrder<'info> {
     pub escrow_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
 }
 
 pub fn handler(ctx: Context<CancelLimitOrder>) -> Result<()> {
@@ -58,26 +67,35 @@ pub fn handler(ctx: Context<CancelLimitOrder>) -> Result<()> {
     if remaining_amount > 0 {
         // Return escrowed tokens to user
         let order_book_key = order_book.key();
+        let order_index_bytes = limit_order.order_index.to_le_bytes();
         let seeds = &[
             b"limit_order",
             limit_order.owner.as_ref(),
             order_book_key.as_ref(),
+            order_index_bytes.as_ref(),
             &[limit_order.bump],
         ];
         let signer = &[&seeds[..]];
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.escrow_token_account.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: limit_order.to_account_info(),
-                },
-                signer,
+        // A wrapped SOL escrow can be closed directly, unwrapping its balance to the user
+        match &ctx.accounts.user_token_account {
+            Some(user_token_account) => token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: user_token_account.to_account_info(),
+                        authority: limit_order.to_account_info(),
+                    },
+                    signer,
+                ),
+                remaining_amount,
+            )?,
+            None => require!(
+                ctx.accounts.escrow_token_account.is_native(),
+                SwapError::InvalidNativeSolAccounts
             ),
-            remaining_amount,
-        )?;
+        }
 
         // Close escrow account
         token::close_account(
@@ -95,6 +113,9 @@ pub fn handler(ctx: Context<CancelLimitOrder>) -> Result<()> {
 
     // Update order status
     limit_order.order_status = OrderStatus::Cancelled;
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.remove_order(&limit_order.key());
+    }
 
     // Update order book counts
     match limit_order.order_side {
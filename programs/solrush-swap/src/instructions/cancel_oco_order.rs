@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer, CloseAccount};
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+pub struct CancelOcoOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"oco_order",
+            user.key().as_ref(),
+            oco_order.pool.as_ref(),
+            &oco_order.order_id.to_le_bytes()
+        ],
+        bump = oco_order.bump,
+        constraint = oco_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
+        constraint = oco_order.order_status != OrderStatus::Cancelled @ SwapError::OrderAlreadyCancelled,
+        close = user
+    )]
+    pub oco_order: Account<'info, OcoOrder>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = oco_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
+}
+
+pub fn handler(ctx: Context<CancelOcoOrder>) -> Result<()> {
+    let oco_order = &mut ctx.accounts.oco_order;
+    let remaining_amount = ctx.accounts.escrow_token_account.amount;
+
+    let order_id_bytes = oco_order.order_id.to_le_bytes();
+    let seeds = &[
+        b"oco_order",
+        oco_order.owner.as_ref(),
+        oco_order.pool.as_ref(),
+        order_id_bytes.as_ref(),
+        &[oco_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if remaining_amount > 0 {
+        // Return escrowed tokens to user
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: oco_order.to_account_info(),
+                },
+                signer,
+            ),
+            remaining_amount,
+        )?;
+    }
+
+    // Close the shared escrow
+    token::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: oco_order.to_account_info(),
+            },
+            signer,
+        ),
+    )?;
+
+    // A filled order is only being closed; an open one cancels both legs
+    if oco_order.order_status == OrderStatus::Open {
+        oco_order.order_status = OrderStatus::Cancelled;
+    }
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.remove_order(&oco_order.key());
+    }
+
+    msg!("OCO order closed: {} returned to user", remaining_amount);
+
+    Ok(())
+}
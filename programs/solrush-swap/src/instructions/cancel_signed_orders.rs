@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+pub struct CancelSignedOrders<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"signed_order_nonce", owner.key().as_ref()],
+        bump = signed_order_nonce.bump
+    )]
+    pub signed_order_nonce: Account<'info, SignedOrderNonce>,
+}
+
+pub fn handler(ctx: Context<CancelSignedOrders>, next_nonce: u64) -> Result<()> {
+    let signed_order_nonce = &mut ctx.accounts.signed_order_nonce;
+
+    // Nonces only move forward so a used order can never become valid again
+    require!(next_nonce > signed_order_nonce.next_nonce, SwapError::InvalidNonce);
+    signed_order_nonce.next_nonce = next_nonce;
+
+    msg!("Signed orders with nonce below {} cancelled", next_nonce);
+
+    Ok(())
+}
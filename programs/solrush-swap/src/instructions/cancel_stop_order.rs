@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer, CloseAccount};
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+pub struct CancelStopOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"stop_order",
+            user.key().as_ref(),
+            stop_order.pool.as_ref(),
+            &stop_order.order_id.to_le_bytes()
+        ],
+        bump = stop_order.bump,
+        constraint = stop_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
+        constraint = stop_order.order_status != OrderStatus::Cancelled @ SwapError::OrderAlreadyCancelled,
+        close = user
+    )]
+    pub stop_order: Account<'info, StopOrder>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = stop_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
+}
+
+pub fn handler(ctx: Context<CancelStopOrder>) -> Result<()> {
+    let stop_order = &mut ctx.accounts.stop_order;
+    let remaining_amount = ctx.accounts.escrow_token_account.amount;
+
+    let order_id_bytes = stop_order.order_id.to_le_bytes();
+    let seeds = &[
+        b"stop_order",
+        stop_order.owner.as_ref(),
+        stop_order.pool.as_ref(),
+        order_id_bytes.as_ref(),
+        &[stop_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    if remaining_amount > 0 {
+        // Return escrowed tokens to user
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: stop_order.to_account_info(),
+                },
+                signer,
+            ),
+            remaining_amount,
+        )?;
+    }
+
+    // Close escrow account
+    token::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.user.to_account_info(),
+                authority: stop_order.to_account_info(),
+            },
+            signer,
+        ),
+    )?;
+
+    // A filled order is only being closed; an open one is cancelled
+    if stop_order.order_status == OrderStatus::Open {
+        stop_order.order_status = OrderStatus::Cancelled;
+    }
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.remove_order(&stop_order.key());
+    }
+
+    msg!("Stop order closed: {} returned to user", remaining_amount);
+
+    Ok(())
+}
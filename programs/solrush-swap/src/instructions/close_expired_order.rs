@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer, CloseAccount};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::utils::EXPIRED_ORDER_CLOSE_BOUNTY;
+
+#[derive(Accounts)]
+pub struct CloseExpiredOrder<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", limit_order.trading_pair.seed()],
+        bump = order_book.bump
+    )]
+    pub order_book: Account<'info, OrderBook>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"limit_order",
+            limit_order.owner.as_ref(),
+            order_book.key().as_ref(),
+            &limit_order.order_index.to_le_bytes()
+        ],
+        bump = limit_order.bump,
+        constraint = limit_order.order_status == OrderStatus::Open
+            || limit_order.order_status == OrderStatus::PartiallyFilled
+            || limit_order.order_status == OrderStatus::Expired @ SwapError::InvalidOrderStatus,
+        close = order_owner
+    )]
+    pub limit_order: Account<'info, LimitOrder>,
+
+    /// CHECK: Owner of the limit order, receives the refund and rent
+    #[account(
+        mut,
+        address = limit_order.owner @ SwapError::UnauthorizedOrderAccess
+    )]
+    pub order_owner: AccountInfo<'info>,
+
+    /// Omitted to refund a wrapped SOL escrow as native lamports
+    #[account(
+        mut,
+        constraint = order_owner_token_account.owner == order_owner.key(),
+        constraint = order_owner_token_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
+    )]
+    pub order_owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = limit_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Order owner's `UserOrders` index at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_orders", limit_order.owner.as_ref()],
+        bump
+    )]
+    pub user_orders: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<CloseExpiredOrder>) -> Result<()> {
+    let limit_order = &mut ctx.accounts.limit_order;
+    let order_book = &mut ctx.accounts.order_book;
+    let clock = Clock::get()?;
+
+    require!(
+        limit_order.expires_at > 0 && clock.unix_timestamp > limit_order.expires_at,
+        SwapError::OrderNotExpired
+    );
+
+    let remaining_amount = ctx.accounts.escrow_token_account.amount;
+
+    let order_book_key = order_book.key();
+    let order_index_bytes = limit_order.order_index.to_le_bytes();
+    let seeds = &[
+        b"limit_order",
+        limit_order.owner.as_ref(),
+        order_book_key.as_ref(),
+        order_index_bytes.as_ref(),
+        &[limit_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Refund escrowed tokens to order owner; a wrapped SOL escrow is
+    // unwrapped by closing it
+    match &ctx.accounts.order_owner_token_account {
+        Some(order_owner_token_account) if remaining_amount > 0 => token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: order_owner_token_account.to_account_info(),
+                    authority: limit_order.to_account_info(),
+                },
+                signer,
+            ),
+            remaining_amount,
+        )?,
+        Some(_) => {}
+        None => require!(
+            ctx.accounts.escrow_token_account.is_native(),
+            SwapError::InvalidNativeSolAccounts
+        ),
+    }
+
+    // Close escrow account, rent goes to order owner
+    token::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.order_owner.to_account_info(),
+                authority: limit_order.to_account_info(),
+            },
+            signer,
+        ),
+    )?;
+
+    // Pay the sweeper out of the order account rent; the rest goes to the
+    // owner when the account is closed
+    let bounty = if ctx.accounts.caller.key() != limit_order.owner {
+        EXPIRED_ORDER_CLOSE_BOUNTY.min(limit_order.to_account_info().lamports())
+    } else {
+        0
+    };
+    if bounty > 0 {
+        **limit_order.to_account_info().try_borrow_mut_lamports()? -= bounty;
+        **ctx.accounts.caller.to_account_info().try_borrow_mut_lamports()? += bounty;
+    }
+
+    limit_order.order_status = OrderStatus::Expired;
+    UserOrders::remove_order_at(&ctx.accounts.user_orders, &limit_order.key())?;
+
+    // Update order book counts
+    match limit_order.order_side {
+        OrderSide::Buy => {
+            order_book.buy_orders_count = order_book.buy_orders_count.saturating_sub(1);
+        }
+        OrderSide::Sell => {
+            order_book.sell_orders_count = order_book.sell_orders_count.saturating_sub(1);
+        }
+    }
+
+    msg!("Expired limit order closed: {} returned to owner, {} lamports bounty",
+        remaining_amount, bounty);
+
+    Ok(())
+}
@@ -2,8 +2,10 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use crate::state::*;
 use crate::errors::SwapError;
+use crate::utils::validate_dca_parameters;
 
 #[derive(Accounts)]
+#[instruction(order_id: u64)]
 pub struct CreateDCAOrder<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
@@ -21,7 +23,7 @@ pub struct CreateDCAOrder<'info> {
         seeds = [
             b"dca_order",
             user.key().as_ref(),
-            &Clock::get()?.unix_timestamp.to_le_bytes()
+            &order_id.to_le_bytes()
         ],
         bump
     )]
@@ -47,10 +49,20 @@ pub struct CreateDCAOrder<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<CreateDCAOrder>,
+    order_id: u64,
     trading_pair: TradingPair,
     order_side: OrderSide,
     amount_per_cycle: u64,
@@ -60,16 +72,9 @@ pub fn handler(
     min_price: u64,
     max_price: u64,
 ) -> Result<()> {
-    require!(amount_per_cycle > 0, SwapError::InvalidAmount);
-    require!(total_cycles > 0 && total_cycles <= 1000, SwapError::MaxCyclesExceeded);
-    require!(cycle_frequency >= 60, SwapError::InvalidCycleFrequency); // At least 60 seconds
+    validate_dca_parameters(amount_per_cycle, total_cycles, cycle_frequency, min_price, max_price)?;
     require!(slippage_tolerance <= 10000, SwapError::InvalidSlippageTolerance);
 
-    // Validate price range if specified
-    if min_price > 0 && max_price > 0 {
-        require!(min_price < max_price, SwapError::PriceOutOfRange);
-    }
-
     let clock = Clock::get()?;
     let dca_order = &mut ctx.accounts.dca_order;
 
@@ -109,8 +114,13 @@ pub fn handler(
     dca_order.escrow_token_account = ctx.accounts.escrow_token_account.key();
     dca_order.total_amount_in = 0;
     dca_order.total_amount_out = 0;
+    dca_order.order_id = order_id;
     dca_order.bump = ctx.bumps.dca_order;
 
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.add_order(dca_order.key(), OrderType::DCA)?;
+    }
+
     msg!(
         "DCA order created: {} cycles of {} tokens every {} seconds (price range: {} - {})",
         total_cycles,
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+pub struct DepositDCAOrder<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [
+            b"dca_order",
+            user.key().as_ref(),
+            &dca_order.order_id.to_le_bytes()
+        ],
+        bump = dca_order.bump,
+        constraint = dca_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
+        constraint = dca_order.order_status == OrderStatus::Open || dca_order.order_status == OrderStatus::PartiallyFilled @ SwapError::InvalidOrderStatus
+    )]
+    pub dca_order: Account<'info, DCAOrder>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = dca_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<DepositDCAOrder>, amount: u64) -> Result<()> {
+    require!(amount > 0, SwapError::InvalidAmount);
+
+    // Top up escrow
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    msg!(
+        "DCA order topped up: {} deposited, escrow balance {}",
+        amount,
+        ctx.accounts.escrow_token_account.amount.checked_add(amount).ok_or(SwapError::CalculationOverflow)?
+    );
+
+    Ok(())
+}
@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use solrush_admin::{state::FeeSchedule, FEE_SCHEDULE_SEED};
+use solrush_token::{state::StakeAccount, STAKE_SEED};
 use crate::state::*;
 use crate::errors::SwapError;
+use crate::fees::{load_if_initialized, record_user_volume_at, resolve_fee_rate, token_a_volume};
+use crate::oracle::check_oracle_deviation;
+use crate::price::{pool_spot_price, PriceScale};
+use crate::utils::{calculate_swap_with_fee, record_trade, refresh_ema_price};
 
 #[derive(Accounts)]
 pub struct ExecuteDCAOrder<'info> {
@@ -12,7 +18,8 @@ pub struct ExecuteDCAOrder<'info> {
         mut,
         seeds = [b"pool", pool.trading_pair.seed()],
         bump = pool.bump,
-        constraint = !pool.is_paused @ SwapError::PoolPaused
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
     )]
     pub pool: Account<'info, LiquidityPool>,
 
@@ -24,6 +31,7 @@ pub struct ExecuteDCAOrder<'info> {
     pub dca_order: Account<'info, DCAOrder>,
 
     /// CHECK: Owner of the DCA order
+    #[account(address = dca_order.owner @ SwapError::UnauthorizedOrderAccess)]
     pub order_owner: AccountInfo<'info>,
 
     #[account(
@@ -50,7 +58,48 @@ pub struct ExecuteDCAOrder<'info> {
     )]
     pub pool_token_b_vault: Account<'info, TokenAccount>,
 
+    /// CHECK: Pyth-format price account, validated against pool.oracle
+    pub oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    /// CHECK: Admin `FeeSchedule` at its fixed address, read only if it exists
+    #[account(
+        seeds = [FEE_SCHEDULE_SEED],
+        bump,
+        seeds::program = solrush_admin::ID
+    )]
+    pub fee_schedule: UncheckedAccount<'info>,
+
+    /// CHECK: Order owner's `UserVolume` at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_volume", dca_order.owner.as_ref()],
+        bump
+    )]
+    pub user_volume: UncheckedAccount<'info>,
+
+    /// CHECK: Order owner's RUSH `StakeAccount` at its fixed address, read only if it exists
+    #[account(
+        seeds = [STAKE_SEED, dca_order.owner.as_ref()],
+        bump,
+        seeds::program = solrush_token::ID
+    )]
+    pub rush_stake: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
+
+    /// CHECK: Order owner's `UserOrders` index at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_orders", dca_order.owner.as_ref()],
+        bump
+    )]
+    pub user_orders: UncheckedAccount<'info>,
 }
 
 pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
@@ -70,23 +119,22 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
         SwapError::DCACompleted
     );
 
-    // Get current market price
-    let current_price = match dca_order.order_side {
-        OrderSide::Buy => {
-            pool.reserve_b
-                .checked_mul(1_000_000_000)
-                .ok_or(SwapError::CalculationOverflow)?
-                .checked_div(pool.reserve_a)
-                .ok_or(SwapError::MathError)?
-        }
-        OrderSide::Sell => {
-            pool.reserve_a
-                .checked_mul(1_000_000_000)
-                .ok_or(SwapError::CalculationOverflow)?
-                .checked_div(pool.reserve_b)
-                .ok_or(SwapError::MathError)?
-        }
+    // Output goes to the owner in the token the order is buying
+    let output_mint = match dca_order.order_side {
+        OrderSide::Buy => pool.token_b_mint,
+        OrderSide::Sell => pool.token_a_mint,
     };
+    require!(
+        ctx.accounts.order_owner_token_account.mint == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    // Reject execution against a pool pushed away from the oracle price
+    check_oracle_deviation(pool, ctx.accounts.oracle.as_deref(), clock.unix_timestamp)?;
+
+    // Price band is on the pool price, token B per token A, for both sides
+    let price_scale = PriceScale::of(pool);
+    let current_price = pool_spot_price(pool)?;
 
     // Check price range if specified
     if dca_order.min_price > 0 {
@@ -103,6 +151,10 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
     }
 
     let amount_in = dca_order.amount_per_cycle;
+    require!(
+        ctx.accounts.escrow_token_account.amount >= amount_in,
+        SwapError::InsufficientEscrowBalance
+    );
 
     // Calculate swap output using constant product formula
     let (reserve_in, reserve_out, input_is_token_a) = match dca_order.order_side {
@@ -110,25 +162,16 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
         OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
     };
 
-    let fee_adjusted_input = amount_in
-        .checked_mul(10000u64.checked_sub(pool.fee_rate as u64).ok_or(SwapError::MathError)?)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let numerator = reserve_out
-        .checked_mul(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let denominator = reserve_in
-        .checked_mul(10000)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_add(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let amount_out = numerator
-        .checked_div(denominator)
-        .ok_or(SwapError::ZeroOutput)?;
+    let user_volume = load_if_initialized::<UserVolume>(&ctx.accounts.user_volume)?;
+    let fee_rate = resolve_fee_rate(
+        pool,
+        load_if_initialized::<FeeSchedule>(&ctx.accounts.fee_schedule)?.as_ref(),
+        user_volume.as_ref(),
+        load_if_initialized::<StakeAccount>(&ctx.accounts.rush_stake)?.as_ref(),
+        clock.unix_timestamp,
+    );
 
-    require!(amount_out > 0, SwapError::ZeroOutput);
+    let (amount_out, _) = calculate_swap_with_fee(amount_in, reserve_in, reserve_out, fee_rate)?;
 
     // Check slippage
     let minimum_expected = amount_out
@@ -139,11 +182,15 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
 
     require!(amount_out >= minimum_expected, SwapError::SlippageExceeded);
 
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
     // Execute the swap
+    let order_id_bytes = dca_order.order_id.to_le_bytes();
     let seeds = &[
         b"dca_order",
         dca_order.owner.as_ref(),
-        &dca_order.last_execution.to_le_bytes(),
+        order_id_bytes.as_ref(),
         &[dca_order.bump],
     ];
     let signer = &[&seeds[..]];
@@ -228,6 +275,24 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
         pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
     }
 
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        dca_order.owner,
+        OrderType::DCA,
+        dca_order.order_side,
+        amount_in,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    record_user_volume_at(
+        &ctx.accounts.user_volume,
+        user_volume,
+        clock.unix_timestamp,
+        token_a_volume(dca_order.order_side, amount_in, amount_out),
+    )?;
+
     // Update DCA order
     dca_order.cycles_executed += 1;
     dca_order.last_execution = clock.unix_timestamp;
@@ -242,6 +307,7 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
     // Update status
     if dca_order.cycles_executed == dca_order.total_cycles {
         dca_order.order_status = OrderStatus::Filled;
+        UserOrders::remove_order_at(&ctx.accounts.user_orders, &dca_order.key())?;
     } else {
         dca_order.order_status = OrderStatus::PartiallyFilled;
     }
@@ -255,15 +321,9 @@ pub fn handler(ctx: Context<ExecuteDCAOrder>) -> Result<()> {
         amount_in,
         amount_out,
         current_price,
-        if dca_order.total_amount_in > 0 {
-            dca_order.total_amount_out
-                .checked_mul(1_000_000_000)
-                .unwrap_or(0)
-                .checked_div(dca_order.total_amount_in)
-                .unwrap_or(0)
-        } else {
-            0
-        }
+        price_scale
+            .execution_price(dca_order.total_amount_in, dca_order.total_amount_out, dca_order.order_side)
+            .unwrap_or(0)
     );
 
     Ok(())
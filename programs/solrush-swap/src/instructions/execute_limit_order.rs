@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::system_program::{self, Transfer as SystemTransfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
+use solrush_admin::{state::FeeSchedule, FEE_SCHEDULE_SEED};
+use solrush_token::{state::StakeAccount, STAKE_SEED};
 use crate::state::*;
 use crate::errors::SwapError;
+use crate::native_sol::unwrap_sol;
+use crate::fees::{calculate_maker_rebate, load_if_initialized, record_user_volume_at, resolve_fee_rate, token_a_volume};
+use crate::oracle::check_oracle_deviation;
+use crate::price::PriceScale;
+use crate::utils::{calculate_swap_with_fee, is_limit_price_met, record_trade, refresh_ema_price};
 
 #[derive(Accounts)]
 pub struct ExecuteLimitOrder<'info> {
@@ -12,7 +20,8 @@ pub struct ExecuteLimitOrder<'info> {
         mut,
         seeds = [b"pool", pool.trading_pair.seed()],
         bump = pool.bump,
-        constraint = !pool.is_paused @ SwapError::PoolPaused
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
     )]
     pub pool: Account<'info, LiquidityPool>,
 
@@ -30,14 +39,28 @@ pub struct ExecuteLimitOrder<'info> {
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
-    /// CHECK: Owner of the limit order
+    /// CHECK: Owner of the limit order; receives native SOL payouts
+    #[account(
+        mut,
+        address = limit_order.owner @ SwapError::UnauthorizedOrderAccess
+    )]
     pub order_owner: AccountInfo<'info>,
 
+    /// Omitted when paying out SOL to the owner as native lamports
     #[account(
         mut,
         constraint = order_owner_token_account.owner == order_owner.key()
     )]
-    pub order_owner_token_account: Account<'info, TokenAccount>,
+    pub order_owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Owner's input token account the maker rebate is paid to; required
+    /// whenever a rebate is due
+    #[account(
+        mut,
+        constraint = order_owner_rebate_account.owner == limit_order.owner @ SwapError::UnauthorizedOrderAccess,
+        constraint = order_owner_rebate_account.mint == escrow_token_account.mint @ SwapError::InvalidTokenPair
+    )]
+    pub order_owner_rebate_account: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -57,7 +80,63 @@ pub struct ExecuteLimitOrder<'info> {
     )]
     pub pool_token_b_vault: Account<'info, TokenAccount>,
 
+    /// CHECK: Pyth-format price account, validated against pool.oracle
+    pub oracle: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    /// CHECK: Admin `FeeSchedule` at its fixed address, read only if it exists
+    #[account(
+        seeds = [FEE_SCHEDULE_SEED],
+        bump,
+        seeds::program = solrush_admin::ID
+    )]
+    pub fee_schedule: UncheckedAccount<'info>,
+
+    /// CHECK: Order owner's `UserVolume` at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_volume", limit_order.owner.as_ref()],
+        bump
+    )]
+    pub user_volume: UncheckedAccount<'info>,
+
+    /// CHECK: Order owner's RUSH `StakeAccount` at its fixed address, read only if it exists
+    #[account(
+        seeds = [STAKE_SEED, limit_order.owner.as_ref()],
+        bump,
+        seeds::program = solrush_token::ID
+    )]
+    pub rush_stake: UncheckedAccount<'info>,
+
+    /// Temporary wrapped SOL account for a native payout, closed before the instruction ends
+    #[account(
+        init,
+        payer = executor,
+        seeds = [b"native_sol", executor.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = executor
+    )]
+    pub native_sol_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Account<'info, Mint>>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Order owner's `UserOrders` index at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_orders", limit_order.owner.as_ref()],
+        bump
+    )]
+    pub user_orders: UncheckedAccount<'info>,
 }
 
 pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
@@ -66,36 +145,20 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
     let order_book = &mut ctx.accounts.order_book;
     let clock = Clock::get()?;
 
-    // Check if order has expired
-    if limit_order.expires_at > 0 && clock.unix_timestamp > limit_order.expires_at {
-        limit_order.order_status = OrderStatus::Expired;
-        return Err(SwapError::OrderExpired.into());
-    }
+    // Expired orders are swept by close_expired_order
+    require!(
+        limit_order.expires_at == 0 || clock.unix_timestamp <= limit_order.expires_at,
+        SwapError::OrderExpired
+    );
 
-    // Get current market price from pool
-    let current_price = if limit_order.order_side == OrderSide::Buy {
-        // Buying token B, price in token B per token A
-        pool.reserve_b
-            .checked_mul(1_000_000_000)
-            .ok_or(SwapError::CalculationOverflow)?
-            .checked_div(pool.reserve_a)
-            .ok_or(SwapError::MathError)?
-    } else {
-        // Selling token B, price in token A per token B
-        pool.reserve_a
-            .checked_mul(1_000_000_000)
-            .ok_or(SwapError::CalculationOverflow)?
-            .checked_div(pool.reserve_b)
-            .ok_or(SwapError::MathError)?
-    };
+    // Reject execution against a pool pushed away from the oracle price
+    check_oracle_deviation(pool, ctx.accounts.oracle.as_deref(), clock.unix_timestamp)?;
 
     // Check if limit price is reached
-    let price_met = match limit_order.order_side {
-        OrderSide::Buy => current_price <= limit_order.limit_price,
-        OrderSide::Sell => current_price >= limit_order.limit_price,
-    };
-
-    require!(price_met, SwapError::LimitPriceNotReached);
+    require!(
+        is_limit_price_met(pool, limit_order.order_side, limit_order.limit_price)?,
+        SwapError::LimitPriceNotReached
+    );
 
     // Calculate remaining amount to fill
     let remaining_amount = limit_order.amount_in
@@ -110,25 +173,37 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
         OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
     };
 
-    let fee_adjusted_input = remaining_amount
-        .checked_mul(10000u64.checked_sub(pool.fee_rate as u64).ok_or(SwapError::MathError)?)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let numerator = reserve_out
-        .checked_mul(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let denominator = reserve_in
-        .checked_mul(10000)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_add(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let amount_out = numerator
-        .checked_div(denominator)
-        .ok_or(SwapError::ZeroOutput)?;
-
-    require!(amount_out > 0, SwapError::ZeroOutput);
+    // A native payout is only possible when the order receives wrapped SOL,
+    // and the temporary account is passed exactly when it is used
+    let native_output = ctx.accounts.order_owner_token_account.is_none();
+    require!(
+        !native_output || (!input_is_token_a && pool.token_a_mint == native_mint::ID),
+        SwapError::InvalidNativeSolAccounts
+    );
+    require!(
+        ctx.accounts.native_sol_account.is_some() == native_output,
+        SwapError::InvalidNativeSolAccounts
+    );
+
+    // Resting orders provide liquidity, so the owner pays the tier fee and
+    // gets the maker rebate of it back out of the pool
+    let fee_schedule = load_if_initialized::<FeeSchedule>(&ctx.accounts.fee_schedule)?;
+    let user_volume = load_if_initialized::<UserVolume>(&ctx.accounts.user_volume)?;
+    let rush_stake = load_if_initialized::<StakeAccount>(&ctx.accounts.rush_stake)?;
+    let fee_rate = resolve_fee_rate(
+        pool,
+        fee_schedule.as_ref(),
+        user_volume.as_ref(),
+        rush_stake.as_ref(),
+        clock.unix_timestamp,
+    );
+
+    let (amount_out, fee_amount) = calculate_swap_with_fee(remaining_amount, reserve_in, reserve_out, fee_rate)?;
+    let maker_rebate = calculate_maker_rebate(fee_amount, fee_schedule.as_ref());
+
+    // The average fill price must also be at or better than the limit
+    let limit_out = PriceScale::of(pool).output_at_price(remaining_amount, limit_order.limit_price, limit_order.order_side)?;
+    require!(amount_out >= limit_out, SwapError::LimitPriceNotReached);
 
     // Calculate expected minimum with slippage
     let minimum_expected = amount_out
@@ -139,16 +214,30 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
 
     require!(amount_out >= minimum_expected, SwapError::SlippageExceeded);
 
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
     // Execute the swap
     let order_book_key = order_book.key();
+    let order_index_bytes = limit_order.order_index.to_le_bytes();
     let seeds = &[
         b"limit_order",
         limit_order.owner.as_ref(),
         order_book_key.as_ref(),
+        order_index_bytes.as_ref(),
         &[limit_order.bump],
     ];
     let signer = &[&seeds[..]];
 
+    // Native SOL is paid into the temporary account in place of the owner's token account
+    let owner_token_out = match &ctx.accounts.order_owner_token_account {
+        Some(account) => account.to_account_info(),
+        None => ctx.accounts.native_sol_account
+            .as_ref()
+            .map(|account| account.to_account_info())
+            .ok_or(SwapError::InvalidNativeSolAccounts)?,
+    };
+
     if input_is_token_a {
         // Transfer token A from escrow to pool
         token::transfer(
@@ -177,7 +266,7 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.pool_token_b_vault.to_account_info(),
-                    to: ctx.accounts.order_owner_token_account.to_account_info(),
+                    to: owner_token_out.clone(),
                     authority: pool.to_account_info(),
                 },
                 pool_signer,
@@ -216,7 +305,7 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.pool_token_a_vault.to_account_info(),
-                    to: ctx.accounts.order_owner_token_account.to_account_info(),
+                    to: owner_token_out.clone(),
                     authority: pool.to_account_info(),
                 },
                 pool_signer,
@@ -229,9 +318,87 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
         pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
     }
 
+    // Pay the maker rebate out of the fee the fill left in the input vault
+    if maker_rebate > 0 {
+        let rebate_account = ctx.accounts.order_owner_rebate_account
+            .as_ref()
+            .ok_or(SwapError::MissingRebateAccount)?;
+        let pool_seeds = &[
+            b"pool",
+            pool.trading_pair.seed(),
+            &[pool.bump],
+        ];
+        let pool_signer = &[&pool_seeds[..]];
+        let input_vault = if input_is_token_a {
+            ctx.accounts.pool_token_a_vault.to_account_info()
+        } else {
+            ctx.accounts.pool_token_b_vault.to_account_info()
+        };
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: input_vault,
+                    to: rebate_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                pool_signer,
+            ),
+            maker_rebate,
+        )?;
+
+        if input_is_token_a {
+            pool.reserve_a = pool.reserve_a.checked_sub(maker_rebate).ok_or(SwapError::MathError)?;
+        } else {
+            pool.reserve_b = pool.reserve_b.checked_sub(maker_rebate).ok_or(SwapError::MathError)?;
+        }
+    }
+
+    // The executor gets its rent back on close and forwards the output to the owner
+    if native_output {
+        let executor = ctx.accounts.executor.to_account_info();
+        unwrap_sol(
+            &owner_token_out,
+            &executor,
+            &executor,
+            &ctx.accounts.token_program.to_account_info(),
+            &[],
+        )?;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                SystemTransfer {
+                    from: executor,
+                    to: ctx.accounts.order_owner.to_account_info(),
+                },
+            ),
+            amount_out,
+        )?;
+    }
+
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        limit_order.owner,
+        OrderType::Limit,
+        limit_order.order_side,
+        remaining_amount,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    record_user_volume_at(
+        &ctx.accounts.user_volume,
+        user_volume,
+        clock.unix_timestamp,
+        token_a_volume(limit_order.order_side, remaining_amount, amount_out),
+    )?;
+
     // Update order status
     limit_order.amount_filled = limit_order.amount_in;
     limit_order.order_status = OrderStatus::Filled;
+    UserOrders::remove_order_at(&ctx.accounts.user_orders, &limit_order.key())?;
     pool.last_update_timestamp = clock.unix_timestamp;
 
     // Update order book volume
@@ -239,8 +406,8 @@ pub fn handler(ctx: Context<ExecuteLimitOrder>) -> Result<()> {
         .checked_add(remaining_amount)
         .ok_or(SwapError::CalculationOverflow)?;
 
-    msg!("Limit order executed: {} in -> {} out at market price {}", 
-        remaining_amount, amount_out, current_price);
+    msg!("Limit order executed: {} in -> {} out at limit price {}, fee rate: {}bps, maker rebate: {}",
+        remaining_amount, amount_out, limit_order.limit_price, fee_rate, maker_rebate);
 
     Ok(())
 }
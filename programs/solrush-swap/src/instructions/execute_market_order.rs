@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
+use solrush_admin::{state::FeeSchedule, FEE_SCHEDULE_SEED};
+use solrush_token::{state::StakeAccount, STAKE_SEED};
 use crate::state::*;
 use crate::errors::SwapError;
+use crate::native_sol::{unwrap_sol, wrap_sol};
+use crate::fees::{record_user_volume, resolve_fee_rate, token_a_volume};
+use crate::price::PriceScale;
+use crate::utils::{calculate_max_fill_input, calculate_price_impact, calculate_swap_with_fee, record_trade, refresh_ema_price};
 
 #[derive(Accounts)]
 pub struct ExecuteMarketOrder<'info> {
@@ -12,21 +18,24 @@ pub struct ExecuteMarketOrder<'info> {
         mut,
         seeds = [b"pool", pool.trading_pair.seed()],
         bump = pool.bump,
-        constraint = !pool.is_paused @ SwapError::PoolPaused
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
     )]
     pub pool: Account<'info, LiquidityPool>,
 
+    /// Omitted when paying SOL as native lamports
     #[account(
         mut,
         constraint = user_token_in.owner == user.key()
     )]
-    pub user_token_in: Account<'info, TokenAccount>,
+    pub user_token_in: Option<Account<'info, TokenAccount>>,
 
+    /// Omitted when receiving SOL as native lamports
     #[account(
         mut,
         constraint = user_token_out.owner == user.key()
     )]
-    pub user_token_out: Account<'info, TokenAccount>,
+    pub user_token_out: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -40,18 +49,46 @@ pub struct ExecuteMarketOrder<'info> {
     )]
     pub pool_token_b_vault: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    #[account(
+        seeds = [FEE_SCHEDULE_SEED],
+        bump = fee_schedule.bump,
+        seeds::program = solrush_admin::ID
+    )]
+    pub fee_schedule: Option<Account<'info, FeeSchedule>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_volume", user.key().as_ref()],
+        bump = user_volume.bump
+    )]
+    pub user_volume: Option<Account<'info, UserVolume>>,
+
+    #[account(
+        seeds = [STAKE_SEED, user.key().as_ref()],
+        bump = rush_stake.bump,
+        seeds::program = solrush_token::ID
+    )]
+    pub rush_stake: Option<Account<'info, StakeAccount>>,
+
+    /// Temporary wrapped SOL account for the native side, closed before the instruction ends
     #[account(
         init,
         payer = user,
-        space = MarketOrderRecord::LEN,
-        seeds = [
-            b"market_order",
-            user.key().as_ref(),
-            &Clock::get()?.unix_timestamp.to_le_bytes()
-        ],
-        bump
+        seeds = [b"native_sol", user.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = user
     )]
-    pub order_record: Account<'info, MarketOrderRecord>,
+    pub native_sol_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Account<'info, Mint>>,
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
@@ -63,6 +100,7 @@ pub fn handler(
     minimum_amount_out: u64,
     slippage_tolerance: u16,
     order_side: OrderSide,
+    time_in_force: TimeInForce,
 ) -> Result<()> {
     require!(amount_in > 0, SwapError::InvalidAmount);
     require!(slippage_tolerance <= 10000, SwapError::InvalidSlippageTolerance);
@@ -70,6 +108,15 @@ pub fn handler(
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
+    // Volume tier and staking discounts apply when the fee schedule is passed
+    let fee_rate = resolve_fee_rate(
+        pool,
+        ctx.accounts.fee_schedule.as_deref(),
+        ctx.accounts.user_volume.as_deref(),
+        ctx.accounts.rush_stake.as_deref(),
+        clock.unix_timestamp,
+    );
+
     // Determine input/output based on order side
     let (reserve_in, reserve_out, input_is_token_a) = match order_side {
         OrderSide::Buy => {
@@ -84,65 +131,89 @@ pub fn handler(
 
     require!(reserve_in > 0 && reserve_out > 0, SwapError::InsufficientLiquidity);
 
-    // Calculate swap output using constant product formula
-    // output = (reserveOut * amountIn * (10000 - fee)) / (reserveIn * 10000 + amountIn * (10000 - fee))
-    let fee_adjusted_input = amount_in
-        .checked_mul(10000u64.checked_sub(pool.fee_rate as u64).ok_or(SwapError::MathError)?)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let numerator = reserve_out
-        .checked_mul(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let denominator = reserve_in
-        .checked_mul(10000)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_add(fee_adjusted_input)
-        .ok_or(SwapError::CalculationOverflow)?;
-    
-    let amount_out = numerator
-        .checked_div(denominator)
-        .ok_or(SwapError::ZeroOutput)?;
-
-    require!(amount_out > 0, SwapError::ZeroOutput);
-    require!(amount_out >= minimum_amount_out, SwapError::SlippageExceeded);
-
-    // Calculate fee amount
-    let fee_amount = amount_in
-        .checked_mul(pool.fee_rate as u64)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_div(10000)
-        .ok_or(SwapError::MathError)?;
+    // Only the wrapped SOL side (token A) can be paid or received as lamports,
+    // and the temporary account is passed exactly when one side is native
+    let native_input = ctx.accounts.user_token_in.is_none();
+    let native_output = ctx.accounts.user_token_out.is_none();
+    if native_input || native_output {
+        require!(
+            pool.token_a_mint == native_mint::ID
+                && native_input == input_is_token_a
+                && native_output != input_is_token_a,
+            SwapError::InvalidNativeSolAccounts
+        );
+    }
+    require!(
+        ctx.accounts.native_sol_account.is_some() == (native_input || native_output),
+        SwapError::InvalidNativeSolAccounts
+    );
 
-    // Calculate price impact
-    let expected_price = reserve_out
-        .checked_mul(1_000_000_000)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_div(reserve_in)
-        .ok_or(SwapError::MathError)?;
-    
-    let actual_price = amount_out
-        .checked_mul(1_000_000_000)
-        .ok_or(SwapError::CalculationOverflow)?
-        .checked_div(amount_in)
-        .ok_or(SwapError::MathError)?;
-    
-    let price_impact = if expected_price > actual_price {
-        ((expected_price - actual_price) as u128)
-            .checked_mul(10000)
-            .ok_or(SwapError::CalculationOverflow)?
-            .checked_div(expected_price as u128)
-            .ok_or(SwapError::MathError)? as u16
-    } else {
-        0
+    // Size the fill by time in force. IOC takes the largest input that keeps
+    // the average price at the requested minimum; the unfilled remainder is
+    // never pulled from the user. FOK must fill the whole input.
+    let (fill_amount, minimum_fill_out) = match time_in_force {
+        TimeInForce::FillOrKill => (amount_in, minimum_amount_out),
+        TimeInForce::ImmediateOrCancel => {
+            let fill_amount = calculate_max_fill_input(
+                amount_in,
+                minimum_amount_out,
+                reserve_in,
+                reserve_out,
+                fee_rate,
+            )?;
+            require!(fill_amount > 0, SwapError::ImmediateOrCancelNotFilled);
+            let minimum_fill_out = (minimum_amount_out as u128)
+                .checked_mul(fill_amount as u128)
+                .ok_or(SwapError::CalculationOverflow)?
+                .checked_div(amount_in as u128)
+                .ok_or(SwapError::MathError)? as u64;
+            (fill_amount, minimum_fill_out)
+        }
+        _ => return Err(SwapError::InvalidTimeInForce.into()),
     };
 
+    // Calculate swap output and fee using the constant product formula
+    let (amount_out, fee_amount) = calculate_swap_with_fee(fill_amount, reserve_in, reserve_out, fee_rate)?;
+
+    if time_in_force == TimeInForce::FillOrKill {
+        require!(amount_out >= minimum_fill_out, SwapError::FillOrKillNotFilled);
+    } else {
+        require!(amount_out >= minimum_fill_out, SwapError::SlippageExceeded);
+    }
+
+    // Calculate price impact
+    let price_impact = calculate_price_impact(fill_amount, amount_out, reserve_in, reserve_out)?;
+
     // Check slippage tolerance
     require!(
         price_impact <= slippage_tolerance,
         SwapError::SlippageExceeded
     );
 
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
+    // Native SOL goes through the temporary account in place of the user's token account
+    let native_sol_account = ctx.accounts.native_sol_account.as_ref().map(|account| account.to_account_info());
+    let user_token_in = match &ctx.accounts.user_token_in {
+        Some(account) => account.to_account_info(),
+        None => native_sol_account.clone().ok_or(SwapError::InvalidNativeSolAccounts)?,
+    };
+    let user_token_out = match &ctx.accounts.user_token_out {
+        Some(account) => account.to_account_info(),
+        None => native_sol_account.clone().ok_or(SwapError::InvalidNativeSolAccounts)?,
+    };
+
+    if native_input {
+        wrap_sol(
+            &ctx.accounts.user.to_account_info(),
+            &user_token_in,
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            fill_amount,
+        )?;
+    }
+
     // Execute token transfers based on order side
     if input_is_token_a {
         // User sends token A, receives token B
@@ -151,12 +222,12 @@ pub fn handler(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.user_token_in.to_account_info(),
+                    from: user_token_in.clone(),
                     to: ctx.accounts.pool_token_a_vault.to_account_info(),
                     authority: ctx.accounts.user.to_account_info(),
                 },
             ),
-            amount_in,
+            fill_amount,
         )?;
 
         // Transfer token B from pool to user
@@ -172,7 +243,7 @@ pub fn handler(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.pool_token_b_vault.to_account_info(),
-                    to: ctx.accounts.user_token_out.to_account_info(),
+                    to: user_token_out.clone(),
                     authority: pool.to_account_info(),
                 },
                 signer,
@@ -181,7 +252,7 @@ pub fn handler(
         )?;
 
         // Update reserves
-        pool.reserve_a = pool.reserve_a.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_a = pool.reserve_a.checked_add(fill_amount).ok_or(SwapError::CalculationOverflow)?;
         pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(SwapError::MathError)?;
         pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
     } else {
@@ -191,12 +262,12 @@ pub fn handler(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.user_token_in.to_account_info(),
+                    from: user_token_in.clone(),
                     to: ctx.accounts.pool_token_b_vault.to_account_info(),
                     authority: ctx.accounts.user.to_account_info(),
                 },
             ),
-            amount_in,
+            fill_amount,
         )?;
 
         // Transfer token A from pool to user
@@ -212,7 +283,7 @@ pub fn handler(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.pool_token_a_vault.to_account_info(),
-                    to: ctx.accounts.user_token_out.to_account_info(),
+                    to: user_token_out.clone(),
                     authority: pool.to_account_info(),
                 },
                 signer,
@@ -221,27 +292,72 @@ pub fn handler(
         )?;
 
         // Update reserves
-        pool.reserve_b = pool.reserve_b.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_b = pool.reserve_b.checked_add(fill_amount).ok_or(SwapError::CalculationOverflow)?;
         pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
         pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
     }
 
+    // Return the wrapped lamports (output or leftover) and the rent to the user
+    if let Some(native_sol_account) = &native_sol_account {
+        unwrap_sol(
+            native_sol_account,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &[],
+        )?;
+    }
+
     pool.last_update_timestamp = clock.unix_timestamp;
 
-    // Record the order execution
-    let order_record = &mut ctx.accounts.order_record;
-    order_record.owner = ctx.accounts.user.key();
-    order_record.trading_pair = pool.trading_pair;
-    order_record.order_side = order_side;
-    order_record.amount_in = amount_in;
-    order_record.amount_out = amount_out;
-    order_record.fee_paid = fee_amount;
-    order_record.price_impact = price_impact;
-    order_record.executed_at = clock.unix_timestamp;
-    order_record.tx_signature = [0u8; 64]; // Will be filled by client
-
-    msg!("Market order executed: {} in -> {} out, fee: {}, impact: {}bps",
-        amount_in, amount_out, fee_amount, price_impact);
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        ctx.accounts.user.key(),
+        OrderType::Market,
+        order_side,
+        fill_amount,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    record_user_volume(
+        ctx.accounts.user_volume.as_deref_mut(),
+        clock.unix_timestamp,
+        token_a_volume(order_side, fill_amount, amount_out),
+    )?;
+
+    emit!(MarketOrderExecuted {
+        user: ctx.accounts.user.key(),
+        pool: pool.key(),
+        trading_pair: pool.trading_pair,
+        order_side,
+        time_in_force,
+        amount_in: fill_amount,
+        amount_unfilled: amount_in - fill_amount,
+        amount_out,
+        fee_paid: fee_amount,
+        price_impact,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Market order executed: {} in -> {} out, fee: {}, impact: {}bps, unfilled: {}",
+        fill_amount, amount_out, fee_amount, price_impact, amount_in - fill_amount);
 
     Ok(())
 }
+
+#[event]
+pub struct MarketOrderExecuted {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub trading_pair: TradingPair,
+    pub order_side: OrderSide,
+    pub time_in_force: TimeInForce,
+    pub amount_in: u64,
+    pub amount_unfilled: u64,
+    pub amount_out: u64,
+    pub fee_paid: u64,
+    pub price_impact: u16,
+    pub timestamp: i64,
+}
@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::price::PriceScale;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ExecuteOcoOrder<'info> {
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        constraint = oco_order.order_status == OrderStatus::Open @ SwapError::InvalidOrderStatus,
+        constraint = oco_order.pool == pool.key()
+    )]
+    pub oco_order: Account<'info, OcoOrder>,
+
+    /// CHECK: Owner of the OCO order
+    #[account(address = oco_order.owner @ SwapError::UnauthorizedOrderAccess)]
+    pub order_owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = order_owner_token_account.owner == order_owner.key()
+    )]
+    pub order_owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = oco_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault
+    )]
+    pub pool_token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_b_vault
+    )]
+    pub pool_token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Order owner's `UserOrders` index at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_orders", oco_order.owner.as_ref()],
+        bump
+    )]
+    pub user_orders: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<ExecuteOcoOrder>, leg: OcoLeg) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let oco_order = &mut ctx.accounts.oco_order;
+    let clock = Clock::get()?;
+
+    // Check if order has expired
+    require!(
+        oco_order.expires_at == 0 || clock.unix_timestamp <= oco_order.expires_at,
+        SwapError::OrderExpired
+    );
+
+    // Output goes to the owner in the token the order is buying
+    let output_mint = match oco_order.order_side {
+        OrderSide::Buy => pool.token_b_mint,
+        OrderSide::Sell => pool.token_a_mint,
+    };
+    require!(
+        ctx.accounts.order_owner_token_account.mint == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
+    let price_scale = PriceScale::of(pool);
+    let amount_in = oco_order.amount_in;
+    let (reserve_in, reserve_out, input_is_token_a) = match oco_order.order_side {
+        OrderSide::Buy => (pool.reserve_a, pool.reserve_b, true),
+        OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
+    };
+
+    let (amount_out, fee_amount) = calculate_swap_with_fee(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        pool.fee_rate,
+    )?;
+
+    let minimum_amount_out = match leg {
+        // Take profit fills no worse than its limit price
+        OcoLeg::TakeProfit => {
+            let limit_out = price_scale.output_at_price(amount_in, oco_order.take_profit_price, oco_order.order_side)?;
+            require!(amount_out >= limit_out, SwapError::LimitPriceNotReached);
+            limit_out
+        }
+        // Stop fires on the EMA price, then fills like a stop-limit or stop-market
+        OcoLeg::StopLoss => {
+            let reference_price = pool.ema_price;
+            let triggered = match oco_order.stop_trigger_condition() {
+                TriggerCondition::AtOrBelow => reference_price <= oco_order.stop_trigger_price,
+                TriggerCondition::AtOrAbove => reference_price >= oco_order.stop_trigger_price,
+            };
+            require!(reference_price > 0 && triggered, SwapError::StopPriceNotTriggered);
+
+            if oco_order.stop_limit_price > 0 {
+                let limit_out = price_scale.output_at_price(amount_in, oco_order.stop_limit_price, oco_order.order_side)?;
+                require!(amount_out >= limit_out, SwapError::LimitPriceNotReached);
+                limit_out
+            } else {
+                let expected_out = price_scale.output_at_price(amount_in, reference_price, oco_order.order_side)?;
+                let minimum_out = (expected_out as u128)
+                    .checked_mul(10000u128 - oco_order.slippage_tolerance as u128)
+                    .ok_or(SwapError::CalculationOverflow)?
+                    .checked_div(10000u128)
+                    .ok_or(SwapError::MathError)? as u64;
+                require!(amount_out >= minimum_out, SwapError::SlippageExceeded);
+                minimum_out
+            }
+        }
+    };
+
+    // Execute the swap out of the shared escrow
+    let pool_key = pool.key();
+    let order_id_bytes = oco_order.order_id.to_le_bytes();
+    let seeds = &[
+        b"oco_order",
+        oco_order.owner.as_ref(),
+        pool_key.as_ref(),
+        order_id_bytes.as_ref(),
+        &[oco_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let pool_seeds = &[
+        b"pool",
+        pool.trading_pair.seed(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let (vault_in, vault_out) = if input_is_token_a {
+        (&ctx.accounts.pool_token_a_vault, &ctx.accounts.pool_token_b_vault)
+    } else {
+        (&ctx.accounts.pool_token_b_vault, &ctx.accounts.pool_token_a_vault)
+    };
+
+    // Transfer input from escrow to pool
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: oco_order.to_account_info(),
+            },
+            signer,
+        ),
+        amount_in,
+    )?;
+
+    // Transfer output from pool to order owner
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: vault_out.to_account_info(),
+                to: ctx.accounts.order_owner_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            pool_signer,
+        ),
+        amount_out,
+    )?;
+
+    // Update reserves
+    if input_is_token_a {
+        pool.reserve_a = pool.reserve_a.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    } else {
+        pool.reserve_b = pool.reserve_b.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    }
+    pool.last_update_timestamp = clock.unix_timestamp;
+
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        oco_order.owner,
+        OrderType::Oco,
+        oco_order.order_side,
+        amount_in,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    // Filling one leg ends the other; the owner closes the spent escrow with cancel_oco_order
+    oco_order.order_status = OrderStatus::Filled;
+    oco_order.filled_leg = Some(leg);
+    UserOrders::remove_order_at(&ctx.accounts.user_orders, &oco_order.key())?;
+
+    msg!("OCO order executed ({}): {} in -> {} out (min: {})",
+        match leg {
+            OcoLeg::TakeProfit => "take profit",
+            OcoLeg::StopLoss => "stop loss",
+        },
+        amount_in, amount_out, minimum_amount_out);
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::price::PriceScale;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ExecuteStopOrder<'info> {
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        constraint = stop_order.order_status == OrderStatus::Open @ SwapError::InvalidOrderStatus,
+        constraint = stop_order.pool == pool.key()
+    )]
+    pub stop_order: Account<'info, StopOrder>,
+
+    /// CHECK: Owner of the stop order
+    #[account(address = stop_order.owner @ SwapError::UnauthorizedOrderAccess)]
+    pub order_owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = order_owner_token_account.owner == order_owner.key()
+    )]
+    pub order_owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = stop_order.escrow_token_account
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault
+    )]
+    pub pool_token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_b_vault
+    )]
+    pub pool_token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Order owner's `UserOrders` index at its fixed address, updated only if it exists
+    #[account(
+        mut,
+        seeds = [b"user_orders", stop_order.owner.as_ref()],
+        bump
+    )]
+    pub user_orders: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<ExecuteStopOrder>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let stop_order = &mut ctx.accounts.stop_order;
+    let clock = Clock::get()?;
+
+    // Check if order has expired
+    require!(
+        stop_order.expires_at == 0 || clock.unix_timestamp <= stop_order.expires_at,
+        SwapError::OrderExpired
+    );
+
+    // Output goes to the owner in the token the order is buying
+    let output_mint = match stop_order.order_side {
+        OrderSide::Buy => pool.token_b_mint,
+        OrderSide::Sell => pool.token_a_mint,
+    };
+    require!(
+        ctx.accounts.order_owner_token_account.mint == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    // Trigger against the EMA price so a same-transaction pool push cannot fire the order
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+    let reference_price = pool.ema_price;
+
+    let triggered = match stop_order.trigger_condition {
+        TriggerCondition::AtOrBelow => reference_price <= stop_order.trigger_price,
+        TriggerCondition::AtOrAbove => reference_price >= stop_order.trigger_price,
+    };
+    require!(reference_price > 0 && triggered, SwapError::StopPriceNotTriggered);
+
+    let price_scale = PriceScale::of(pool);
+    let amount_in = stop_order.amount_in;
+    let (reserve_in, reserve_out, input_is_token_a) = match stop_order.order_side {
+        OrderSide::Buy => (pool.reserve_a, pool.reserve_b, true),
+        OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
+    };
+
+    let (amount_out, fee_amount) = calculate_swap_with_fee(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        pool.fee_rate,
+    )?;
+
+    // Stop-limit fills no worse than the limit price; stop-market and
+    // trailing stops fill within slippage tolerance of the EMA price
+    let minimum_amount_out = match stop_order.order_type {
+        OrderType::StopLimit => {
+            let limit_out = price_scale.output_at_price(amount_in, stop_order.limit_price, stop_order.order_side)?;
+            require!(amount_out >= limit_out, SwapError::LimitPriceNotReached);
+            limit_out
+        }
+        _ => {
+            let expected_out = price_scale.output_at_price(amount_in, reference_price, stop_order.order_side)?;
+            let minimum_out = (expected_out as u128)
+                .checked_mul(10000u128 - stop_order.slippage_tolerance as u128)
+                .ok_or(SwapError::CalculationOverflow)?
+                .checked_div(10000u128)
+                .ok_or(SwapError::MathError)? as u64;
+            require!(amount_out >= minimum_out, SwapError::SlippageExceeded);
+            minimum_out
+        }
+    };
+
+    // Execute the swap
+    let pool_key = pool.key();
+    let order_id_bytes = stop_order.order_id.to_le_bytes();
+    let seeds = &[
+        b"stop_order",
+        stop_order.owner.as_ref(),
+        pool_key.as_ref(),
+        order_id_bytes.as_ref(),
+        &[stop_order.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let pool_seeds = &[
+        b"pool",
+        pool.trading_pair.seed(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let (vault_in, vault_out) = if input_is_token_a {
+        (&ctx.accounts.pool_token_a_vault, &ctx.accounts.pool_token_b_vault)
+    } else {
+        (&ctx.accounts.pool_token_b_vault, &ctx.accounts.pool_token_a_vault)
+    };
+
+    // Transfer input from escrow to pool
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: stop_order.to_account_info(),
+            },
+            signer,
+        ),
+        amount_in,
+    )?;
+
+    // Transfer output from pool to order owner
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: vault_out.to_account_info(),
+                to: ctx.accounts.order_owner_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            pool_signer,
+        ),
+        amount_out,
+    )?;
+
+    // Update reserves
+    if input_is_token_a {
+        pool.reserve_a = pool.reserve_a.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    } else {
+        pool.reserve_b = pool.reserve_b.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    }
+    pool.last_update_timestamp = clock.unix_timestamp;
+
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        stop_order.owner,
+        stop_order.order_type,
+        stop_order.order_side,
+        amount_in,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    stop_order.order_status = OrderStatus::Filled;
+    UserOrders::remove_order_at(&ctx.accounts.user_orders, &stop_order.key())?;
+
+    msg!("Stop order executed: {} in -> {} out (min: {}) at EMA price {}",
+        amount_in, amount_out, minimum_amount_out, reference_price);
+
+    Ok(())
+}
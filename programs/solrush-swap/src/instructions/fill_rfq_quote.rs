@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use solrush_admin::{constants::MARKET_MAKER_SEED, state::MarketMaker};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::ed25519::verify_ed25519_signature;
+use crate::price::PriceScale;
+
+#[derive(Accounts)]
+#[instruction(quote: RfqQuote)]
+pub struct FillRfqQuote<'info> {
+    #[account(
+        mut,
+        address = quote.taker @ SwapError::UnauthorizedOrderAccess
+    )]
+    pub taker: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = pool.key() == quote.pool @ SwapError::InvalidRfqQuote,
+        constraint = !pool.is_paused @ SwapError::PoolPaused
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    /// Admin-managed whitelist entry for the quoting maker
+    #[account(
+        seeds = [MARKET_MAKER_SEED, quote.maker.as_ref()],
+        bump = market_maker.bump,
+        seeds::program = solrush_admin::ID
+    )]
+    pub market_maker: Account<'info, MarketMaker>,
+
+    #[account(
+        init,
+        payer = taker,
+        space = RfqFill::LEN,
+        seeds = [b"rfq_fill", quote.maker.as_ref(), &quote.nonce.to_le_bytes()],
+        bump
+    )]
+    pub rfq_fill: Account<'info, RfqFill>,
+
+    /// CHECK: PDA the maker approves as delegate on their output token account
+    #[account(
+        seeds = [b"order_delegate"],
+        bump
+    )]
+    pub order_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = taker_token_in.owner == taker.key()
+    )]
+    pub taker_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = taker_token_out.owner == taker.key()
+    )]
+    pub taker_token_out: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = maker_token_in.owner == quote.maker @ SwapError::UnauthorizedOrderAccess,
+        constraint = maker_token_in.mint == taker_token_in.mint @ SwapError::InvalidTokenPair
+    )]
+    pub maker_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = maker_token_out.owner == quote.maker @ SwapError::UnauthorizedOrderAccess,
+        constraint = maker_token_out.mint == taker_token_out.mint @ SwapError::InvalidTokenPair
+    )]
+    pub maker_token_out: Account<'info, TokenAccount>,
+
+    /// CHECK: Instructions sysvar, read to find the ed25519 signature check
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<FillRfqQuote>, quote: RfqQuote) -> Result<()> {
+    require!(quote.amount_in > 0 && quote.amount_out > 0, SwapError::InvalidRfqQuote);
+
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp <= quote.expires_at, SwapError::OrderExpired);
+
+    // The previous instruction must be the ed25519 check of this exact quote by its maker
+    verify_ed25519_signature(
+        &ctx.accounts.instructions.to_account_info(),
+        &quote.maker,
+        &quote.message()?,
+    )?;
+
+    let pool = &ctx.accounts.pool;
+    let (input_mint, output_mint) = match quote.order_side {
+        OrderSide::Buy => (pool.token_a_mint, pool.token_b_mint),
+        OrderSide::Sell => (pool.token_b_mint, pool.token_a_mint),
+    };
+    require!(
+        ctx.accounts.taker_token_in.mint == input_mint
+            && ctx.accounts.taker_token_out.mint == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    // Transfer input from taker to maker
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.taker_token_in.to_account_info(),
+                to: ctx.accounts.maker_token_in.to_account_info(),
+                authority: ctx.accounts.taker.to_account_info(),
+            },
+        ),
+        quote.amount_in,
+    )?;
+
+    // Transfer output from maker to taker through the delegate
+    let delegate_seeds = &[
+        b"order_delegate".as_ref(),
+        &[ctx.bumps.order_delegate],
+    ];
+    let delegate_signer = &[&delegate_seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.maker_token_out.to_account_info(),
+                to: ctx.accounts.taker_token_out.to_account_info(),
+                authority: ctx.accounts.order_delegate.to_account_info(),
+            },
+            delegate_signer,
+        ),
+        quote.amount_out,
+    )?;
+
+    let rfq_fill = &mut ctx.accounts.rfq_fill;
+    rfq_fill.maker = quote.maker;
+    rfq_fill.taker = quote.taker;
+    rfq_fill.nonce = quote.nonce;
+    rfq_fill.filled_at = clock.unix_timestamp;
+    rfq_fill.bump = ctx.bumps.rfq_fill;
+
+    let price = PriceScale::of(pool).execution_price(quote.amount_in, quote.amount_out, quote.order_side)?;
+
+    emit!(RfqQuoteFilled {
+        maker: quote.maker,
+        taker: quote.taker,
+        pool: pool.key(),
+        trading_pair: pool.trading_pair,
+        order_side: quote.order_side,
+        nonce: quote.nonce,
+        amount_in: quote.amount_in,
+        amount_out: quote.amount_out,
+        price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("RFQ quote {} from {} filled: {} in -> {} out",
+        quote.nonce, quote.maker, quote.amount_in, quote.amount_out);
+
+    Ok(())
+}
+
+#[event]
+pub struct RfqQuoteFilled {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub pool: Pubkey,
+    pub trading_pair: TradingPair,
+    pub order_side: OrderSide,
+    pub nonce: u64,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub price: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,245 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::ed25519::verify_ed25519_signature;
+use crate::price::PriceScale;
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(order: SignedOrder)]
+pub struct FillSignedOrder<'info> {
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = pool.key() == order.pool @ SwapError::InvalidTokenPair,
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"signed_order_nonce", order.owner.as_ref()],
+        bump = signed_order_nonce.bump
+    )]
+    pub signed_order_nonce: Account<'info, SignedOrderNonce>,
+
+    /// CHECK: PDA the owner approves as delegate on their input token account
+    #[account(
+        seeds = [b"order_delegate"],
+        bump
+    )]
+    pub order_delegate: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = owner_token_in.owner == order.owner @ SwapError::UnauthorizedOrderAccess
+    )]
+    pub owner_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_out.owner == order.owner @ SwapError::UnauthorizedOrderAccess
+    )]
+    pub owner_token_out: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = relayer_fee_account.mint == owner_token_in.mint @ SwapError::InvalidTokenPair
+    )]
+    pub relayer_fee_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault
+    )]
+    pub pool_token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_b_vault
+    )]
+    pub pool_token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    /// CHECK: Instructions sysvar, read to find the ed25519 signature check
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<FillSignedOrder>, order: SignedOrder) -> Result<()> {
+    require!(order.amount_in > 0, SwapError::InvalidAmount);
+
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp <= order.expires_at, SwapError::OrderExpired);
+
+    // The previous instruction must be the ed25519 check of this exact order by its owner
+    verify_ed25519_signature(
+        &ctx.accounts.instructions.to_account_info(),
+        &order.owner,
+        &order.message()?,
+    )?;
+
+    // Consume the nonce; every lower nonce becomes invalid too
+    let signed_order_nonce = &mut ctx.accounts.signed_order_nonce;
+    require!(order.nonce >= signed_order_nonce.next_nonce, SwapError::InvalidNonce);
+    signed_order_nonce.next_nonce = order.nonce.checked_add(1).ok_or(SwapError::CalculationOverflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+
+    let (input_mint, output_mint) = match order.order_side {
+        OrderSide::Buy => (pool.token_a_mint, pool.token_b_mint),
+        OrderSide::Sell => (pool.token_b_mint, pool.token_a_mint),
+    };
+    require!(
+        ctx.accounts.owner_token_in.mint == input_mint
+            && ctx.accounts.owner_token_out.mint == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    let (reserve_in, reserve_out, input_is_token_a) = match order.order_side {
+        OrderSide::Buy => (pool.reserve_a, pool.reserve_b, true),
+        OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
+    };
+
+    let (amount_out, fee_amount) = calculate_swap_with_fee(
+        order.amount_in,
+        reserve_in,
+        reserve_out,
+        pool.fee_rate,
+    )?;
+    require!(amount_out >= order.minimum_amount_out, SwapError::SlippageExceeded);
+
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
+    let delegate_seeds = &[
+        b"order_delegate".as_ref(),
+        &[ctx.bumps.order_delegate],
+    ];
+    let delegate_signer = &[&delegate_seeds[..]];
+
+    let pool_seeds = &[
+        b"pool",
+        pool.trading_pair.seed(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let (vault_in, vault_out) = if input_is_token_a {
+        (&ctx.accounts.pool_token_a_vault, &ctx.accounts.pool_token_b_vault)
+    } else {
+        (&ctx.accounts.pool_token_b_vault, &ctx.accounts.pool_token_a_vault)
+    };
+
+    // Transfer input from owner to pool through the delegate
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: ctx.accounts.order_delegate.to_account_info(),
+            },
+            delegate_signer,
+        ),
+        order.amount_in,
+    )?;
+
+    // Pay the relayer its signed fee
+    if order.relayer_fee > 0 {
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_token_in.to_account_info(),
+                    to: ctx.accounts.relayer_fee_account.to_account_info(),
+                    authority: ctx.accounts.order_delegate.to_account_info(),
+                },
+                delegate_signer,
+            ),
+            order.relayer_fee,
+        )?;
+    }
+
+    // Transfer output from pool to owner
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: vault_out.to_account_info(),
+                to: ctx.accounts.owner_token_out.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            pool_signer,
+        ),
+        amount_out,
+    )?;
+
+    // Update reserves
+    if input_is_token_a {
+        pool.reserve_a = pool.reserve_a.checked_add(order.amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    } else {
+        pool.reserve_b = pool.reserve_b.checked_add(order.amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    }
+    pool.last_update_timestamp = clock.unix_timestamp;
+
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        order.owner,
+        OrderType::Market,
+        order.order_side,
+        order.amount_in,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(SignedOrderFilled {
+        owner: order.owner,
+        relayer: ctx.accounts.relayer.key(),
+        pool: pool.key(),
+        order_side: order.order_side,
+        nonce: order.nonce,
+        amount_in: order.amount_in,
+        amount_out,
+        fee_paid: fee_amount,
+        relayer_fee: order.relayer_fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Signed order {} filled: {} in -> {} out, relayer fee: {}",
+        order.nonce, order.amount_in, amount_out, order.relayer_fee);
+
+    Ok(())
+}
+
+#[event]
+pub struct SignedOrderFilled {
+    pub owner: Pubkey,
+    pub relayer: Pubkey,
+    pub pool: Pubkey,
+    pub order_side: OrderSide,
+    pub nonce: u64,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_paid: u64,
+    pub relayer_fee: u64,
+    pub timestamp: i64,
+}
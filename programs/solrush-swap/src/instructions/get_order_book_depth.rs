@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::price::PriceScale;
+use crate::utils::*;
+
+/// Resting limit orders of the order book are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetOrderBookDepth<'info> {
+    #[account(
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        seeds = [b"order_book", pool.trading_pair.seed()],
+        bump = order_book.bump
+    )]
+    pub order_book: Account<'info, OrderBook>,
+}
+
+pub fn handler(
+    ctx: Context<GetOrderBookDepth>,
+    min_price: u64,
+    max_price: u64,
+    num_levels: u8,
+) -> Result<OrderBookDepth> {
+    require!(
+        min_price > 0 && min_price < max_price && (2..=MAX_DEPTH_LEVELS).contains(&num_levels),
+        SwapError::InvalidDepthRange
+    );
+
+    let pool = &ctx.accounts.pool;
+    let order_book_key = ctx.accounts.order_book.key();
+    let price_scale = PriceScale::of(pool);
+    let spot_price = price_scale.spot_price(pool.reserve_a, pool.reserve_b)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Resting orders as (side, limit price, token A size)
+    let mut orders = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut seen = BTreeSet::new();
+    for account in ctx.remaining_accounts.iter() {
+        // The same order passed twice would be counted twice
+        require!(seen.insert(account.key()), SwapError::DuplicateOrderAccount);
+        require_keys_eq!(*account.owner, crate::ID, SwapError::InvalidOrderAccount);
+        let limit_order = LimitOrder::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        require_keys_eq!(limit_order.order_book, order_book_key, SwapError::InvalidOrderAccount);
+
+        let resting = limit_order.order_status == OrderStatus::Open
+            || limit_order.order_status == OrderStatus::PartiallyFilled;
+        let expired = limit_order.expires_at > 0 && now > limit_order.expires_at;
+        if !resting || expired || limit_order.limit_price == 0 {
+            continue;
+        }
+
+        let remaining = limit_order.amount_in.saturating_sub(limit_order.amount_filled);
+        // Buy orders sell token A; sell orders spend token B on token A
+        let size = match limit_order.order_side {
+            OrderSide::Buy => remaining,
+            OrderSide::Sell => price_scale.output_at_price(remaining, limit_order.limit_price, OrderSide::Sell)?,
+        };
+        orders.push((limit_order.order_side, limit_order.limit_price, size));
+    }
+
+    let mut bids = Vec::new();
+    let mut asks = Vec::new();
+    let range = (max_price - min_price) as u128;
+
+    for i in 0..num_levels as u128 {
+        let price = min_price + (range * i / (num_levels as u128 - 1)) as u64;
+        if price == spot_price {
+            continue;
+        }
+
+        let is_bid = price < spot_price;
+        let amm_amount = calculate_amm_depth(pool.reserve_a, pool.reserve_b, pool.fee_rate, price, price_scale)?;
+
+        // A seller of token A down to `price` meets sell orders priced at or
+        // above it; a buyer up to `price` meets buy orders priced at or below
+        let order_amount = orders
+            .iter()
+            .filter(|(side, limit_price, _)| match side {
+                OrderSide::Sell => is_bid && *limit_price >= price,
+                OrderSide::Buy => !is_bid && *limit_price <= price,
+            })
+            .try_fold(0u64, |total, (_, _, size)| total.checked_add(*size))
+            .ok_or(SwapError::CalculationOverflow)?;
+
+        let level = DepthLevel {
+            price,
+            amm_amount,
+            order_amount,
+        };
+        if is_bid {
+            bids.push(level);
+        } else {
+            asks.push(level);
+        }
+    }
+
+    // Bids run outward from spot like asks
+    bids.reverse();
+
+    Ok(OrderBookDepth {
+        spot_price,
+        bids,
+        asks,
+    })
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetUserOrders<'info> {
+    #[account(
+        seeds = [b"user_orders", user_orders.owner.as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Account<'info, UserOrders>,
+}
+
+pub fn handler(ctx: Context<GetUserOrders>) -> Result<Vec<UserOrderEntry>> {
+    Ok(ctx.accounts.user_orders.orders.clone())
+}
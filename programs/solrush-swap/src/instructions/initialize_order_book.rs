@@ -38,6 +38,7 @@ pub fn handler(
     order_book.sell_orders_count = 0;
     order_book.total_volume = 0;
     order_book.bump = ctx.bumps.order_book;
+    order_book.next_order_id = 0;
 
     msg!("Order book initialized for {:?}", trading_pair);
 
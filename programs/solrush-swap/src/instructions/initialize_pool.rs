@@ -60,6 +60,8 @@ pub fn handler(
     pool.trading_pair = trading_pair;
     pool.token_a_mint = ctx.accounts.token_a_mint.key();
     pool.token_b_mint = ctx.accounts.token_b_mint.key();
+    pool.token_a_decimals = ctx.accounts.token_a_mint.decimals;
+    pool.token_b_decimals = ctx.accounts.token_b_mint.decimals;
     pool.token_a_vault = ctx.accounts.token_a_vault.key();
     pool.token_b_vault = ctx.accounts.token_b_vault.key();
     pool.lp_token_mint = ctx.accounts.lp_token_mint.key();
@@ -70,6 +72,11 @@ pub fn handler(
     pool.total_fees_a = 0;
     pool.total_fees_b = 0;
     pool.last_update_timestamp = Clock::get()?.unix_timestamp;
+    pool.ema_price = 0;
+    pool.ema_last_update = pool.last_update_timestamp;
+    pool.oracle = Pubkey::default();
+    pool.max_oracle_deviation = 0;
+    pool.trade_history = Pubkey::default();
     pool.is_paused = false;
     pool.bump = ctx.bumps.pool;
 
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeSignedOrderNonce<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Owner of the signed orders; any payer (e.g. a relayer) may create the account
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = SignedOrderNonce::LEN,
+        seeds = [b"signed_order_nonce", owner.key().as_ref()],
+        bump
+    )]
+    pub signed_order_nonce: Account<'info, SignedOrderNonce>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeSignedOrderNonce>) -> Result<()> {
+    let signed_order_nonce = &mut ctx.accounts.signed_order_nonce;
+
+    signed_order_nonce.owner = ctx.accounts.owner.key();
+    signed_order_nonce.next_nonce = 0;
+    signed_order_nonce.bump = ctx.bumps.signed_order_nonce;
+
+    msg!("Signed order nonce initialized for {}", signed_order_nonce.owner);
+
+    Ok(())
+}
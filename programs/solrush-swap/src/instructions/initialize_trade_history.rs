@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeTradeHistory<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TradeHistory::LEN,
+        seeds = [b"trade_history", pool.key().as_ref()],
+        bump
+    )]
+    pub trade_history: AccountLoader<'info, TradeHistory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeTradeHistory>) -> Result<()> {
+    let mut trade_history = ctx.accounts.trade_history.load_init()?;
+
+    trade_history.pool = ctx.accounts.pool.key();
+    trade_history.total_trades = 0;
+    trade_history.head = 0;
+    trade_history.bump = ctx.bumps.trade_history;
+
+    // From here on every fill in the pool must be recorded
+    ctx.accounts.pool.trade_history = ctx.accounts.trade_history.key();
+
+    msg!("Trade history initialized for pool {}", trade_history.pool);
+
+    Ok(())
+}
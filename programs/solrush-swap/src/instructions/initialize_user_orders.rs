@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeUserOrders<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserOrders::LEN,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump
+    )]
+    pub user_orders: Account<'info, UserOrders>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeUserOrders>) -> Result<()> {
+    let user_orders = &mut ctx.accounts.user_orders;
+
+    user_orders.owner = ctx.accounts.user.key();
+    user_orders.orders = Vec::new();
+    user_orders.bump = ctx.bumps.user_orders;
+
+    msg!("User order index initialized for {}", user_orders.owner);
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeUserVolume<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserVolume::LEN,
+        seeds = [b"user_volume", user.key().as_ref()],
+        bump
+    )]
+    pub user_volume: Account<'info, UserVolume>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeUserVolume>) -> Result<()> {
+    let user_volume = &mut ctx.accounts.user_volume;
+
+    user_volume.owner = ctx.accounts.user.key();
+    user_volume.daily_volume = [0; VOLUME_WINDOW_DAYS];
+    user_volume.last_day = 0;
+    user_volume.bump = ctx.bumps.user_volume;
+
+    msg!("Trading volume tracking initialized for {}", user_volume.owner);
+
+    Ok(())
+}
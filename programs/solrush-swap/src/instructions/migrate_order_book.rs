@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+#[instruction(trading_pair: TradingPair)]
+pub struct MigrateOrderBook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Legacy-layout order book, which `Account<OrderBook>` cannot
+    /// deserialize; owner, seeds, discriminator and authority are checked here
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"order_book", trading_pair.seed()],
+        bump
+    )]
+    pub order_book: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow an order book created before `next_order_id` and set it
+///
+/// Legacy order indices were the live order count, so they cannot be
+/// recovered on-chain: `next_order_id` must be above the highest index of
+/// any order still open, which the authority reads off-chain.
+pub fn handler(
+    ctx: Context<MigrateOrderBook>,
+    _trading_pair: TradingPair,
+    next_order_id: u64,
+) -> Result<()> {
+    let order_book_info = ctx.accounts.order_book.to_account_info();
+
+    let legacy = {
+        let data = order_book_info.try_borrow_data()?;
+        require!(data.len() == OrderBook::LEGACY_LEN, SwapError::OrderBookAlreadyMigrated);
+        require!(
+            data[..8] == OrderBook::DISCRIMINATOR,
+            SwapError::OrderBookAlreadyMigrated
+        );
+        LegacyOrderBook::deserialize(&mut &data[8..])?
+    };
+
+    require!(legacy.authority == ctx.accounts.authority.key(), SwapError::Unauthorized);
+    let order_count = legacy.buy_orders_count
+        .checked_add(legacy.sell_orders_count)
+        .ok_or(SwapError::CalculationOverflow)?;
+    require!(next_order_id >= order_count, SwapError::InvalidNextOrderId);
+
+    let rent_due = Rent::get()?
+        .minimum_balance(OrderBook::LEN)
+        .saturating_sub(order_book_info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: order_book_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    order_book_info.realloc(OrderBook::LEN, true)?;
+
+    let order_book = OrderBook {
+        authority: legacy.authority,
+        trading_pair: legacy.trading_pair,
+        pool: legacy.pool,
+        buy_orders_count: legacy.buy_orders_count,
+        sell_orders_count: legacy.sell_orders_count,
+        total_volume: legacy.total_volume,
+        bump: legacy.bump,
+        next_order_id,
+    };
+    order_book.try_serialize(&mut &mut order_book_info.try_borrow_mut_data()?[..])?;
+
+    msg!("Order book {} migrated with next order id {}", order_book_info.key(), next_order_id);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token::Mint;
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+#[instruction(trading_pair: TradingPair)]
+pub struct MigratePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Legacy-layout pool, which `Account<LiquidityPool>` cannot
+    /// deserialize; owner, seeds, discriminator and authority are checked here
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"pool", trading_pair.seed()],
+        bump
+    )]
+    pub pool: UncheckedAccount<'info>,
+
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a pool created before the appended `LiquidityPool` fields to the
+/// current size and fill them in: decimals from the mints, no EMA yet and
+/// no oracle check
+pub fn handler(ctx: Context<MigratePool>, _trading_pair: TradingPair) -> Result<()> {
+    let pool_info = ctx.accounts.pool.to_account_info();
+
+    let legacy = {
+        let data = pool_info.try_borrow_data()?;
+        require!(data.len() == LiquidityPool::LEGACY_LEN, SwapError::PoolAlreadyMigrated);
+        require!(
+            data[..8] == LiquidityPool::DISCRIMINATOR,
+            SwapError::PoolAlreadyMigrated
+        );
+        LegacyLiquidityPool::deserialize(&mut &data[8..])?
+    };
+
+    require!(legacy.authority == ctx.accounts.authority.key(), SwapError::Unauthorized);
+    require!(
+        legacy.token_a_mint == ctx.accounts.token_a_mint.key()
+            && legacy.token_b_mint == ctx.accounts.token_b_mint.key(),
+        SwapError::InvalidTokenPair
+    );
+
+    let rent_due = Rent::get()?
+        .minimum_balance(LiquidityPool::LEN)
+        .saturating_sub(pool_info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: pool_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    pool_info.realloc(LiquidityPool::LEN, true)?;
+
+    let pool = LiquidityPool {
+        authority: legacy.authority,
+        trading_pair: legacy.trading_pair,
+        token_a_mint: legacy.token_a_mint,
+        token_b_mint: legacy.token_b_mint,
+        token_a_vault: legacy.token_a_vault,
+        token_b_vault: legacy.token_b_vault,
+        lp_token_mint: legacy.lp_token_mint,
+        reserve_a: legacy.reserve_a,
+        reserve_b: legacy.reserve_b,
+        lp_supply: legacy.lp_supply,
+        fee_rate: legacy.fee_rate,
+        total_fees_a: legacy.total_fees_a,
+        total_fees_b: legacy.total_fees_b,
+        last_update_timestamp: legacy.last_update_timestamp,
+        is_paused: legacy.is_paused,
+        bump: legacy.bump,
+        ema_price: 0,
+        ema_last_update: Clock::get()?.unix_timestamp,
+        token_a_decimals: ctx.accounts.token_a_mint.decimals,
+        token_b_decimals: ctx.accounts.token_b_mint.decimals,
+        oracle: Pubkey::default(),
+        max_oracle_deviation: 0,
+        trade_history: Pubkey::default(),
+    };
+    pool.try_serialize(&mut &mut pool_info.try_borrow_mut_data()?[..])?;
+
+    msg!("Pool {} migrated to the current layout", pool_info.key());
+
+    Ok(())
+}
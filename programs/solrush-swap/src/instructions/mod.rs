@@ -8,8 +8,33 @@ pub mod execute_limit_order;
 pub mod create_dca_order;
 pub mod execute_dca_order;
 pub mod cancel_dca_order;
+pub mod modify_dca_order;
+pub mod deposit_dca_order;
 pub mod initialize_pool;
 pub mod initialize_order_book;
+pub mod migrate_order_book;
+pub mod set_pool_oracle;
+pub mod migrate_pool;
+pub mod initialize_trade_history;
+pub mod place_stop_order;
+pub mod execute_stop_order;
+pub mod cancel_stop_order;
+pub mod place_trailing_stop_order;
+pub mod update_trailing_stop;
+pub mod place_oco_order;
+pub mod place_bracket_order;
+pub mod execute_oco_order;
+pub mod cancel_oco_order;
+pub mod close_expired_order;
+pub mod initialize_user_orders;
+pub mod initialize_user_volume;
+pub mod get_user_orders;
+pub mod prune_user_orders;
+pub mod get_order_book_depth;
+pub mod initialize_signed_order_nonce;
+pub mod fill_signed_order;
+pub mod cancel_signed_orders;
+pub mod fill_rfq_quote;
 
 pub use execute_swap::*;
 pub use calculate_swap_output::*;
@@ -21,5 +46,30 @@ pub use execute_limit_order::*;
 pub use create_dca_order::*;
 pub use execute_dca_order::*;
 pub use cancel_dca_order::*;
+pub use modify_dca_order::*;
+pub use deposit_dca_order::*;
 pub use initialize_pool::*;
 pub use initialize_order_book::*;
+pub use migrate_order_book::*;
+pub use set_pool_oracle::*;
+pub use migrate_pool::*;
+pub use initialize_trade_history::*;
+pub use place_stop_order::*;
+pub use execute_stop_order::*;
+pub use cancel_stop_order::*;
+pub use place_trailing_stop_order::*;
+pub use update_trailing_stop::*;
+pub use place_oco_order::*;
+pub use place_bracket_order::*;
+pub use execute_oco_order::*;
+pub use cancel_oco_order::*;
+pub use close_expired_order::*;
+pub use initialize_user_orders::*;
+pub use initialize_user_volume::*;
+pub use get_user_orders::*;
+pub use prune_user_orders::*;
+pub use get_order_book_depth::*;
+pub use initialize_signed_order_nonce::*;
+pub use fill_signed_order::*;
+pub use cancel_signed_orders::*;
+pub use fill_rfq_quote::*;
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::utils::validate_dca_parameters;
+
+#[derive(Accounts)]
+pub struct ModifyDCAOrder<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"dca_order",
+            user.key().as_ref(),
+            &dca_order.order_id.to_le_bytes()
+        ],
+        bump = dca_order.bump,
+        constraint = dca_order.owner == user.key() @ SwapError::UnauthorizedOrderAccess,
+        constraint = dca_order.order_status == OrderStatus::Open || dca_order.order_status == OrderStatus::PartiallyFilled @ SwapError::InvalidOrderStatus
+    )]
+    pub dca_order: Account<'info, DCAOrder>,
+}
+
+pub fn handler(
+    ctx: Context<ModifyDCAOrder>,
+    amount_per_cycle: u64,
+    total_cycles: u16,
+    cycle_frequency: i64,
+    min_price: u64,
+    max_price: u64,
+) -> Result<()> {
+    validate_dca_parameters(amount_per_cycle, total_cycles, cycle_frequency, min_price, max_price)?;
+
+    let dca_order = &mut ctx.accounts.dca_order;
+
+    // Cannot shrink below the cycles that already ran
+    require!(
+        total_cycles > dca_order.cycles_executed,
+        SwapError::InvalidDCAParameters
+    );
+
+    // Keep the schedule anchored to the last cycle (or creation) time
+    dca_order.next_execution = dca_order.next_execution
+        .checked_sub(dca_order.cycle_frequency)
+        .ok_or(SwapError::MathError)?
+        .checked_add(cycle_frequency)
+        .ok_or(SwapError::CalculationOverflow)?;
+
+    dca_order.amount_per_cycle = amount_per_cycle;
+    dca_order.total_cycles = total_cycles;
+    dca_order.cycle_frequency = cycle_frequency;
+    dca_order.min_price = min_price;
+    dca_order.max_price = max_price;
+
+    msg!(
+        "DCA order modified: {}/{} cycles of {} tokens every {} seconds (price range: {} - {})",
+        dca_order.cycles_executed,
+        total_cycles,
+        amount_per_cycle,
+        cycle_frequency,
+        min_price,
+        max_price
+    );
+
+    Ok(())
+}
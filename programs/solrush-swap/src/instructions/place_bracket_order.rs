@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::price::PriceScale;
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceBracketOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = !pool.is_paused @ SwapError::PoolPaused,
+        constraint = pool.trade_history == Pubkey::default() || trade_history.is_some() @ SwapError::MissingTradeHistory
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        init,
+        payer = user,
+        space = OcoOrder::LEN,
+        seeds = [
+            b"oco_order",
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &order_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub oco_order: Account<'info, OcoOrder>,
+
+    /// Pays the market entry
+    #[account(
+        mut,
+        constraint = user_token_in.owner == user.key()
+    )]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    /// Receives the exit escrow back on cancel
+    #[account(
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == token_mint.key() @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Entry output mint, validated against the pool in the handler
+    pub token_mint: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        token::mint = token_mint,
+        token::authority = oco_order,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_a_vault
+    )]
+    pub pool_token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.token_b_vault
+    )]
+    pub pool_token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = pool.trade_history @ SwapError::InvalidTradeHistory
+    )]
+    pub trade_history: Option<AccountLoader<'info, TradeHistory>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<PlaceBracketOrder>,
+    order_id: u64,
+    entry_side: OrderSide,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    take_profit_price: u64,
+    stop_trigger_price: u64,
+    stop_limit_price: u64,
+    slippage_tolerance: u16,
+    expires_at: i64,
+) -> Result<()> {
+    require!(amount_in > 0, SwapError::InvalidAmount);
+    require!(slippage_tolerance <= 10000, SwapError::InvalidSlippageTolerance);
+
+    // Exit legs trade the entry output back on the opposite side
+    let exit_side = match entry_side {
+        OrderSide::Buy => OrderSide::Sell,
+        OrderSide::Sell => OrderSide::Buy,
+    };
+    validate_oco_prices(exit_side, take_profit_price, stop_trigger_price, stop_limit_price)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let clock = Clock::get()?;
+
+    if expires_at > 0 {
+        require!(expires_at > clock.unix_timestamp, SwapError::InvalidExpirationTime);
+    }
+
+    let (input_mint, output_mint) = match entry_side {
+        OrderSide::Buy => (pool.token_a_mint, pool.token_b_mint),
+        OrderSide::Sell => (pool.token_b_mint, pool.token_a_mint),
+    };
+    require!(
+        ctx.accounts.user_token_in.mint == input_mint
+            && ctx.accounts.token_mint.key() == output_mint,
+        SwapError::InvalidTokenPair
+    );
+
+    let (reserve_in, reserve_out, input_is_token_a) = match entry_side {
+        OrderSide::Buy => (pool.reserve_a, pool.reserve_b, true),
+        OrderSide::Sell => (pool.reserve_b, pool.reserve_a, false),
+    };
+
+    // Market entry
+    let (amount_out, fee_amount) = calculate_swap_with_fee(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        pool.fee_rate,
+    )?;
+    require!(amount_out >= minimum_amount_out, SwapError::SlippageExceeded);
+    validate_price_impact(calculate_price_impact(amount_in, amount_out, reserve_in, reserve_out)?)?;
+
+    // Record the pre-trade price in the EMA before reserves move
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+
+    let pool_seeds = &[
+        b"pool",
+        pool.trading_pair.seed(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let (vault_in, vault_out) = if input_is_token_a {
+        (&ctx.accounts.pool_token_a_vault, &ctx.accounts.pool_token_b_vault)
+    } else {
+        (&ctx.accounts.pool_token_b_vault, &ctx.accounts.pool_token_a_vault)
+    };
+
+    // Transfer entry input from user to pool
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    // Entry output goes straight into the exit escrow
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: vault_out.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            pool_signer,
+        ),
+        amount_out,
+    )?;
+
+    // Update reserves
+    if input_is_token_a {
+        pool.reserve_a = pool.reserve_a.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    } else {
+        pool.reserve_b = pool.reserve_b.checked_add(amount_in).ok_or(SwapError::CalculationOverflow)?;
+        pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(SwapError::MathError)?;
+        pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).ok_or(SwapError::CalculationOverflow)?;
+    }
+    pool.last_update_timestamp = clock.unix_timestamp;
+
+    record_trade(
+        ctx.accounts.trade_history.as_ref(),
+        PriceScale::of(pool),
+        ctx.accounts.user.key(),
+        OrderType::Market,
+        entry_side,
+        amount_in,
+        amount_out,
+        clock.unix_timestamp,
+    )?;
+
+    // Attach the exit OCO
+    let oco_order = &mut ctx.accounts.oco_order;
+    oco_order.owner = ctx.accounts.user.key();
+    oco_order.pool = pool.key();
+    oco_order.trading_pair = pool.trading_pair;
+    oco_order.order_side = exit_side;
+    oco_order.order_status = OrderStatus::Open;
+    oco_order.take_profit_price = take_profit_price;
+    oco_order.stop_trigger_price = stop_trigger_price;
+    oco_order.stop_limit_price = stop_limit_price;
+    oco_order.amount_in = amount_out;
+    oco_order.slippage_tolerance = slippage_tolerance;
+    oco_order.filled_leg = None;
+    oco_order.order_id = order_id;
+    oco_order.created_at = clock.unix_timestamp;
+    oco_order.expires_at = expires_at;
+    oco_order.user_token_account = ctx.accounts.user_token_account.key();
+    oco_order.escrow_token_account = ctx.accounts.escrow_token_account.key();
+    oco_order.bump = ctx.bumps.oco_order;
+
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.add_order(oco_order.key(), OrderType::Oco)?;
+    }
+
+    msg!("Bracket order placed: entry {} in -> {} out (take profit: {}, stop: {}, stop limit: {})",
+        amount_in, amount_out, take_profit_price, stop_trigger_price, stop_limit_price);
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Token, TokenAccount, Transfer};
 use crate::state::*;
 use crate::errors::SwapError;
+use crate::native_sol::wrap_sol;
+use crate::utils::is_limit_price_met;
 
 #[derive(Accounts)]
 pub struct PlaceLimitOrder<'info> {
@@ -30,17 +32,18 @@ pub struct PlaceLimitOrder<'info> {
             b"limit_order",
             user.key().as_ref(),
             order_book.key().as_ref(),
-            &order_book.buy_orders_count.checked_add(order_book.sell_orders_count).unwrap().to_le_bytes()
+            &order_book.next_order_id.to_le_bytes()
         ],
         bump
     )]
     pub limit_order: Account<'info, LimitOrder>,
 
+    /// Omitted when funding a SOL input with native lamports
     #[account(
         mut,
         constraint = user_token_account.owner == user.key()
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
 
     /// CHECK: Token mint account
     pub token_mint: AccountInfo<'info>,
@@ -56,8 +59,17 @@ pub struct PlaceLimitOrder<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<PlaceLimitOrder>,
     trading_pair: TradingPair,
@@ -66,6 +78,7 @@ pub fn handler(
     limit_price: u64,
     slippage_tolerance: u16,
     expires_at: i64,
+    time_in_force: TimeInForce,
 ) -> Result<()> {
     require!(amount_in > 0, SwapError::InvalidAmount);
     require!(limit_price > 0, SwapError::InvalidLimitPrice);
@@ -77,21 +90,60 @@ pub fn handler(
         require!(expires_at > clock.unix_timestamp, SwapError::InvalidExpirationTime);
     }
 
+    // Limit orders rest in the book; immediate fills go through execute_market_order
+    match time_in_force {
+        TimeInForce::GoodTilCancelled => {
+            require!(expires_at == 0, SwapError::InvalidExpirationTime);
+        }
+        TimeInForce::GoodTilTime => {
+            require!(expires_at > 0, SwapError::GoodTilTimeRequiresExpiry);
+        }
+        TimeInForce::PostOnly => {
+            let pool = &ctx.accounts.pool;
+            let marketable = pool.reserve_a > 0
+                && pool.reserve_b > 0
+                && is_limit_price_met(pool, order_side, limit_price)?;
+            require!(!marketable, SwapError::PostOnlyWouldTake);
+        }
+        _ => return Err(SwapError::InvalidTimeInForce.into()),
+    }
+
     let order_book = &mut ctx.accounts.order_book;
     let limit_order = &mut ctx.accounts.limit_order;
-
-    // Transfer tokens to escrow
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.escrow_token_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        ),
-        amount_in,
-    )?;
+    // Indices are never reused, so a new order cannot collide with an open one
+    let order_index = order_book.next_order_id;
+    order_book.next_order_id = order_index
+        .checked_add(1)
+        .ok_or(SwapError::OrderBookFull)?;
+
+    // Transfer tokens to escrow, wrapping lamports straight into a wrapped SOL escrow
+    match &ctx.accounts.user_token_account {
+        Some(user_token_account) => token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: user_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount_in,
+        )?,
+        None => {
+            require_keys_eq!(
+                ctx.accounts.token_mint.key(),
+                native_mint::ID,
+                SwapError::InvalidNativeSolAccounts
+            );
+            wrap_sol(
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.escrow_token_account.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                amount_in,
+            )?;
+        }
+    }
 
     // Initialize limit order
     limit_order.owner = ctx.accounts.user.key();
@@ -105,10 +157,18 @@ pub fn handler(
     limit_order.slippage_tolerance = slippage_tolerance;
     limit_order.created_at = clock.unix_timestamp;
     limit_order.expires_at = expires_at;
-    limit_order.user_token_account = ctx.accounts.user_token_account.key();
+    limit_order.time_in_force = time_in_force;
+    limit_order.order_index = order_index;
+    limit_order.user_token_account = ctx.accounts.user_token_account
+        .as_ref()
+        .map_or(Pubkey::default(), |account| account.key());
     limit_order.escrow_token_account = ctx.accounts.escrow_token_account.key();
     limit_order.bump = ctx.bumps.limit_order;
 
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.add_order(limit_order.key(), OrderType::Limit)?;
+    }
+
     // Update order book counts
     match order_side {
         OrderSide::Buy => {
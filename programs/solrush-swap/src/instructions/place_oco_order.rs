@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::utils::validate_oco_prices;
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOcoOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        init,
+        payer = user,
+        space = OcoOrder::LEN,
+        seeds = [
+            b"oco_order",
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &order_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub oco_order: Account<'info, OcoOrder>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == token_mint.key() @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Token mint account, validated against the pool in the handler
+    pub token_mint: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        token::mint = token_mint,
+        token::authority = oco_order,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<PlaceOcoOrder>,
+    order_id: u64,
+    order_side: OrderSide,
+    amount_in: u64,
+    take_profit_price: u64,
+    stop_trigger_price: u64,
+    stop_limit_price: u64,
+    slippage_tolerance: u16,
+    expires_at: i64,
+) -> Result<()> {
+    require!(amount_in > 0, SwapError::InvalidAmount);
+    require!(slippage_tolerance <= 10000, SwapError::InvalidSlippageTolerance);
+    validate_oco_prices(order_side, take_profit_price, stop_trigger_price, stop_limit_price)?;
+
+    // Escrow holds the input token: token A for buys, token B for sells
+    let pool = &ctx.accounts.pool;
+    let input_mint = match order_side {
+        OrderSide::Buy => pool.token_a_mint,
+        OrderSide::Sell => pool.token_b_mint,
+    };
+    require!(ctx.accounts.token_mint.key() == input_mint, SwapError::InvalidTokenPair);
+
+    let clock = Clock::get()?;
+
+    if expires_at > 0 {
+        require!(expires_at > clock.unix_timestamp, SwapError::InvalidExpirationTime);
+    }
+
+    // Transfer tokens to the shared escrow
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    // Initialize OCO order
+    let oco_order = &mut ctx.accounts.oco_order;
+    oco_order.owner = ctx.accounts.user.key();
+    oco_order.pool = pool.key();
+    oco_order.trading_pair = pool.trading_pair;
+    oco_order.order_side = order_side;
+    oco_order.order_status = OrderStatus::Open;
+    oco_order.take_profit_price = take_profit_price;
+    oco_order.stop_trigger_price = stop_trigger_price;
+    oco_order.stop_limit_price = stop_limit_price;
+    oco_order.amount_in = amount_in;
+    oco_order.slippage_tolerance = slippage_tolerance;
+    oco_order.filled_leg = None;
+    oco_order.order_id = order_id;
+    oco_order.created_at = clock.unix_timestamp;
+    oco_order.expires_at = expires_at;
+    oco_order.user_token_account = ctx.accounts.user_token_account.key();
+    oco_order.escrow_token_account = ctx.accounts.escrow_token_account.key();
+    oco_order.bump = ctx.bumps.oco_order;
+
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.add_order(oco_order.key(), OrderType::Oco)?;
+    }
+
+    msg!("OCO order placed: {} {} (take profit: {}, stop: {}, stop limit: {})",
+        amount_in,
+        match order_side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        },
+        take_profit_price,
+        stop_trigger_price,
+        stop_limit_price
+    );
+
+    Ok(())
+}
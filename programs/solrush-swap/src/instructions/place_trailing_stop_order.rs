@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::utils::{calculate_trailing_trigger, refresh_ema_price};
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceTrailingStopOrder<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        init,
+        payer = user,
+        space = StopOrder::LEN,
+        seeds = [
+            b"stop_order",
+            user.key().as_ref(),
+            pool.key().as_ref(),
+            &order_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub stop_order: Account<'info, StopOrder>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == token_mint.key() @ SwapError::InvalidTokenPair
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Token mint account, validated against the pool in the handler
+    pub token_mint: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        token::mint = token_mint,
+        token::authority = stop_order,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Optional order index; omitted by clients that do not keep one
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Option<Account<'info, UserOrders>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<PlaceTrailingStopOrder>,
+    order_id: u64,
+    order_side: OrderSide,
+    amount_in: u64,
+    trailing_offset: u64,
+    trailing_offset_bps: u16,
+    slippage_tolerance: u16,
+    expires_at: i64,
+) -> Result<()> {
+    require!(amount_in > 0, SwapError::InvalidAmount);
+    require!(slippage_tolerance <= 10000, SwapError::InvalidSlippageTolerance);
+
+    // Exactly one of the fixed and percentage offsets
+    require!(
+        (trailing_offset > 0) != (trailing_offset_bps > 0) && trailing_offset_bps < 10000,
+        SwapError::InvalidTriggerPrice
+    );
+
+    // Escrow holds the input token: token A for buys, token B for sells
+    let pool = &mut ctx.accounts.pool;
+    let input_mint = match order_side {
+        OrderSide::Buy => pool.token_a_mint,
+        OrderSide::Sell => pool.token_b_mint,
+    };
+    require!(ctx.accounts.token_mint.key() == input_mint, SwapError::InvalidTokenPair);
+
+    let clock = Clock::get()?;
+
+    if expires_at > 0 {
+        require!(expires_at > clock.unix_timestamp, SwapError::InvalidExpirationTime);
+    }
+
+    // Trail from the current EMA price, against the order side: buys receive
+    // token B and lose when the price falls, sells lose when it rises
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+    let best_price = pool.ema_price;
+    require!(best_price > 0, SwapError::InsufficientLiquidity);
+
+    let trigger_condition = match order_side {
+        OrderSide::Buy => TriggerCondition::AtOrBelow,
+        OrderSide::Sell => TriggerCondition::AtOrAbove,
+    };
+    let trigger_price = calculate_trailing_trigger(
+        best_price,
+        trailing_offset,
+        trailing_offset_bps,
+        trigger_condition,
+    )?;
+
+    // Transfer tokens to escrow
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    // Initialize trailing stop order
+    let stop_order = &mut ctx.accounts.stop_order;
+    stop_order.owner = ctx.accounts.user.key();
+    stop_order.pool = pool.key();
+    stop_order.trading_pair = pool.trading_pair;
+    stop_order.order_type = OrderType::TrailingStop;
+    stop_order.order_side = order_side;
+    stop_order.order_status = OrderStatus::Open;
+    stop_order.trigger_condition = trigger_condition;
+    stop_order.trigger_price = trigger_price;
+    stop_order.limit_price = 0;
+    stop_order.trailing_offset = trailing_offset;
+    stop_order.trailing_offset_bps = trailing_offset_bps;
+    stop_order.best_price = best_price;
+    stop_order.amount_in = amount_in;
+    stop_order.slippage_tolerance = slippage_tolerance;
+    stop_order.order_id = order_id;
+    stop_order.created_at = clock.unix_timestamp;
+    stop_order.expires_at = expires_at;
+    stop_order.user_token_account = ctx.accounts.user_token_account.key();
+    stop_order.escrow_token_account = ctx.accounts.escrow_token_account.key();
+    stop_order.bump = ctx.bumps.stop_order;
+
+    if let Some(user_orders) = &mut ctx.accounts.user_orders {
+        user_orders.add_order(stop_order.key(), OrderType::TrailingStop)?;
+    }
+
+    msg!("Trailing stop placed: {} {} trailing {} (offset: {}, offset bps: {}, trigger: {})",
+        amount_in,
+        match order_side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        },
+        best_price,
+        trailing_offset,
+        trailing_offset_bps,
+        trigger_price
+    );
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct PruneUserOrders<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_orders", user.key().as_ref()],
+        bump = user_orders.bump
+    )]
+    pub user_orders: Account<'info, UserOrders>,
+}
+
+/// Drop entries left behind by orders settled without the index
+pub fn handler(ctx: Context<PruneUserOrders>, orders: Vec<Pubkey>) -> Result<()> {
+    let user_orders = &mut ctx.accounts.user_orders;
+    for order in &orders {
+        user_orders.remove_order(order);
+    }
+
+    msg!("{} orders left in the index of {}", user_orders.orders.len(), user_orders.owner);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SwapError;
+
+#[derive(Accounts)]
+pub struct SetPoolOracle<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ SwapError::Unauthorized
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+}
+
+pub fn handler(
+    ctx: Context<SetPoolOracle>,
+    oracle: Pubkey,
+    max_oracle_deviation: u16,
+) -> Result<()> {
+    if oracle != Pubkey::default() {
+        require!(
+            max_oracle_deviation > 0 && max_oracle_deviation <= 10000,
+            SwapError::InvalidSlippageTolerance
+        );
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.oracle = oracle;
+    pool.max_oracle_deviation = max_oracle_deviation;
+
+    msg!("Pool oracle set to {} with max deviation {}bps", oracle, max_oracle_deviation);
+
+    Ok(())
+}
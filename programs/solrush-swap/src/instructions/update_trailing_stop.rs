@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::SwapError;
+use crate::utils::{calculate_trailing_trigger, is_trailing_price_better, refresh_ema_price};
+
+#[derive(Accounts)]
+pub struct UpdateTrailingStop<'info> {
+    pub executor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", pool.trading_pair.seed()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        constraint = stop_order.order_status == OrderStatus::Open @ SwapError::InvalidOrderStatus,
+        constraint = stop_order.order_type == OrderType::TrailingStop @ SwapError::InvalidOrderType,
+        constraint = stop_order.pool == pool.key()
+    )]
+    pub stop_order: Account<'info, StopOrder>,
+}
+
+pub fn handler(ctx: Context<UpdateTrailingStop>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let stop_order = &mut ctx.accounts.stop_order;
+    let clock = Clock::get()?;
+
+    // Ratchet against the EMA price so a same-transaction pool push cannot
+    // drag the trigger along
+    refresh_ema_price(pool, clock.unix_timestamp)?;
+    let reference_price = pool.ema_price;
+
+    require!(
+        is_trailing_price_better(reference_price, stop_order.best_price, stop_order.trigger_condition),
+        SwapError::TrailingStopNotImproved
+    );
+
+    stop_order.best_price = reference_price;
+    stop_order.trigger_price = calculate_trailing_trigger(
+        reference_price,
+        stop_order.trailing_offset,
+        stop_order.trailing_offset_bps,
+        stop_order.trigger_condition,
+    )?;
+
+    msg!("Trailing stop updated: best price {}, trigger {}",
+        stop_order.best_price, stop_order.trigger_price);
+
+    Ok(())
+}
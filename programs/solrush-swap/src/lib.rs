@@ -2,8 +2,13 @@ use anchor_lang::prelude::*;
 
 declare_id!("SRSwap1111111111111111111111111111111111111");
 
+pub mod ed25519;
 pub mod errors;
+pub mod fees;
 pub mod instructions;
+pub mod native_sol;
+pub mod oracle;
+pub mod price;
 pub mod state;
 pub mod utils;
 
@@ -33,6 +38,45 @@ pub mod solrush_swap {
         instructions::initialize_order_book::handler(ctx, trading_pair)
     }
 
+    /// Resize an order book created before `next_order_id` (order book authority only)
+    ///
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `trading_pair` - Pair the order book belongs to
+    /// * `next_order_id` - Above the highest index of any still-open limit order
+    pub fn migrate_order_book(
+        ctx: Context<MigrateOrderBook>,
+        trading_pair: TradingPair,
+        next_order_id: u64,
+    ) -> Result<()> {
+        instructions::migrate_order_book::handler(ctx, trading_pair, next_order_id)
+    }
+
+    /// Configure the oracle cross-check for limit and DCA execution
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `oracle` - Pyth-format price account for token B per token A (default pubkey disables)
+    /// * `max_oracle_deviation` - Max spot/oracle deviation in basis points
+    pub fn set_pool_oracle(
+        ctx: Context<SetPoolOracle>,
+        oracle: Pubkey,
+        max_oracle_deviation: u16,
+    ) -> Result<()> {
+        instructions::set_pool_oracle::handler(ctx, oracle, max_oracle_deviation)
+    }
+
+    /// Resize a pool created before the appended `LiquidityPool` fields and
+    /// fill them in (pool authority only)
+    pub fn migrate_pool(ctx: Context<MigratePool>, trading_pair: TradingPair) -> Result<()> {
+        instructions::migrate_pool::handler(ctx, trading_pair)
+    }
+
+    /// Create the optional ring buffer of recent trades for a pool
+    pub fn initialize_trade_history(ctx: Context<InitializeTradeHistory>) -> Result<()> {
+        instructions::initialize_trade_history::handler(ctx)
+    }
+
     /// Execute a market order with instant AMM execution
     /// 
     /// # Arguments
@@ -41,12 +85,14 @@ pub mod solrush_swap {
     /// * `minimum_amount_out` - Minimum output amount (slippage protection)
     /// * `slippage_tolerance` - Slippage tolerance in basis points
     /// * `order_side` - Buy or Sell
+    /// * `time_in_force` - ImmediateOrCancel (partial fill, rest stays with the user) or FillOrKill
     pub fn execute_market_order(
         ctx: Context<ExecuteMarketOrder>,
         amount_in: u64,
         minimum_amount_out: u64,
         slippage_tolerance: u16,
         order_side: OrderSide,
+        time_in_force: TimeInForce,
     ) -> Result<()> {
         instructions::execute_market_order::handler(
             ctx,
@@ -54,9 +100,50 @@ pub mod solrush_swap {
             minimum_amount_out,
             slippage_tolerance,
             order_side,
+            time_in_force,
         )
     }
 
+    /// Initialize a user's optional open order index
+    pub fn initialize_user_orders(ctx: Context<InitializeUserOrders>) -> Result<()> {
+        instructions::initialize_user_orders::handler(ctx)
+    }
+
+    /// Remove stale entries from the caller's open order index
+    pub fn prune_user_orders(ctx: Context<PruneUserOrders>, orders: Vec<Pubkey>) -> Result<()> {
+        instructions::prune_user_orders::handler(ctx, orders)
+    }
+
+    /// Initialize a user's rolling 30-day trading volume used for fee tiers
+    pub fn initialize_user_volume(ctx: Context<InitializeUserVolume>) -> Result<()> {
+        instructions::initialize_user_volume::handler(ctx)
+    }
+
+    /// Get a user's open orders
+    pub fn get_user_orders(ctx: Context<GetUserOrders>) -> Result<Vec<UserOrderEntry>> {
+        instructions::get_user_orders::handler(ctx)
+    }
+
+    /// Get aggregated depth of resting limit orders and the AMM curve over a
+    /// price range (view, for simulateTransaction)
+    /// 
+    /// Pass the order book's limit order accounts as remaining accounts.
+    /// Sizes are cumulative token A amounts from the spot price to each level.
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with pool, order book and limit orders
+    /// * `min_price` - Lowest level (token B per token A, scaled by 1e9)
+    /// * `max_price` - Highest level (token B per token A, scaled by 1e9)
+    /// * `num_levels` - Evenly spaced levels from min to max (2 to 32)
+    pub fn get_order_book_depth(
+        ctx: Context<GetOrderBookDepth>,
+        min_price: u64,
+        max_price: u64,
+        num_levels: u8,
+    ) -> Result<OrderBookDepth> {
+        instructions::get_order_book_depth::handler(ctx, min_price, max_price, num_levels)
+    }
+
     /// Place a limit order
     /// 
     /// # Arguments
@@ -64,9 +151,11 @@ pub mod solrush_swap {
     /// * `trading_pair` - Trading pair enum
     /// * `order_side` - Buy or Sell
     /// * `amount_in` - Input token amount
-    /// * `limit_price` - Limit price (scaled by 1e9)
+    /// * `limit_price` - Limit price (token B per token A, scaled by 1e9); buys fill at or above, sells at or below
     /// * `slippage_tolerance` - Slippage tolerance in basis points
     /// * `expires_at` - Expiration timestamp (0 = never expires)
+    /// * `time_in_force` - GoodTilCancelled, GoodTilTime or PostOnly
+    #[allow(clippy::too_many_arguments)]
     pub fn place_limit_order(
         ctx: Context<PlaceLimitOrder>,
         trading_pair: TradingPair,
@@ -75,6 +164,7 @@ pub mod solrush_swap {
         limit_price: u64,
         slippage_tolerance: u16,
         expires_at: i64,
+        time_in_force: TimeInForce,
     ) -> Result<()> {
         instructions::place_limit_order::handler(
             ctx,
@@ -84,6 +174,7 @@ pub mod solrush_swap {
             limit_price,
             slippage_tolerance,
             expires_at,
+            time_in_force,
         )
     }
 
@@ -97,20 +188,31 @@ pub mod solrush_swap {
         instructions::execute_limit_order::handler(ctx)
     }
 
+    /// Close an expired limit order (permissionless)
+    ///
+    /// Refunds the escrow to the owner, returns rent to the owner and pays
+    /// the caller a small bounty out of the reclaimed rent.
+    pub fn close_expired_order(ctx: Context<CloseExpiredOrder>) -> Result<()> {
+        instructions::close_expired_order::handler(ctx)
+    }
+
     /// Create a DCA (Dollar Cost Averaging) order
     /// 
     /// # Arguments
     /// * `ctx` - Context with required accounts
+    /// * `order_id` - Caller-chosen id, unique per user
     /// * `trading_pair` - Trading pair enum
     /// * `order_side` - Buy or Sell
     /// * `amount_per_cycle` - Amount to trade each cycle
     /// * `total_cycles` - Total number of cycles
     /// * `cycle_frequency` - Seconds between cycles
     /// * `slippage_tolerance` - Slippage tolerance in basis points
-    /// * `min_price` - Minimum acceptable price (0 = no limit)
-    /// * `max_price` - Maximum acceptable price (0 = no limit)
+    /// * `min_price` - Minimum pool price, token B per token A scaled by 1e9 (0 = no limit)
+    /// * `max_price` - Maximum pool price, token B per token A scaled by 1e9 (0 = no limit)
+    #[allow(clippy::too_many_arguments)]
     pub fn create_dca_order(
         ctx: Context<CreateDCAOrder>,
+        order_id: u64,
         trading_pair: TradingPair,
         order_side: OrderSide,
         amount_per_cycle: u64,
@@ -122,6 +224,7 @@ pub mod solrush_swap {
     ) -> Result<()> {
         instructions::create_dca_order::handler(
             ctx,
+            order_id,
             trading_pair,
             order_side,
             amount_per_cycle,
@@ -138,11 +241,256 @@ pub mod solrush_swap {
         instructions::execute_dca_order::handler(ctx)
     }
 
-    /// Cancel a DCA order and return remaining funds
+    /// Modify the schedule and price band of an open DCA order
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `amount_per_cycle` - Amount to trade each cycle
+    /// * `total_cycles` - Total number of cycles (including executed ones)
+    /// * `cycle_frequency` - Seconds between cycles
+    /// * `min_price` - Minimum pool price, token B per token A scaled by 1e9 (0 = no limit)
+    /// * `max_price` - Maximum pool price, token B per token A scaled by 1e9 (0 = no limit)
+    pub fn modify_dca_order(
+        ctx: Context<ModifyDCAOrder>,
+        amount_per_cycle: u64,
+        total_cycles: u16,
+        cycle_frequency: i64,
+        min_price: u64,
+        max_price: u64,
+    ) -> Result<()> {
+        instructions::modify_dca_order::handler(
+            ctx,
+            amount_per_cycle,
+            total_cycles,
+            cycle_frequency,
+            min_price,
+            max_price,
+        )
+    }
+
+    /// Top up the escrow of an open DCA order
+    pub fn deposit_dca_order(ctx: Context<DepositDCAOrder>, amount: u64) -> Result<()> {
+        instructions::deposit_dca_order::handler(ctx, amount)
+    }
+
+    /// Cancel a DCA order (or close a completed one) and return remaining funds
     pub fn cancel_dca_order(ctx: Context<CancelDCAOrder>) -> Result<()> {
         instructions::cancel_dca_order::handler(ctx)
     }
 
+    /// Place a stop-market or stop-limit order
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `order_id` - Caller-chosen id, unique per user and pool
+    /// * `order_type` - StopMarket or StopLimit
+    /// * `order_side` - Buy or Sell
+    /// * `trigger_condition` - Fire when the EMA price is at or below / at or above the trigger
+    /// * `trigger_price` - Trigger price (token B per token A, scaled by 1e9)
+    /// * `limit_price` - Worst fill price for StopLimit (0 for StopMarket)
+    /// * `amount_in` - Input token amount
+    /// * `slippage_tolerance` - Slippage tolerance against the EMA price in basis points
+    /// * `expires_at` - Expiration timestamp (0 = never expires)
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_stop_order(
+        ctx: Context<PlaceStopOrder>,
+        order_id: u64,
+        order_type: OrderType,
+        order_side: OrderSide,
+        trigger_condition: TriggerCondition,
+        trigger_price: u64,
+        limit_price: u64,
+        amount_in: u64,
+        slippage_tolerance: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_stop_order::handler(
+            ctx,
+            order_id,
+            order_type,
+            order_side,
+            trigger_condition,
+            trigger_price,
+            limit_price,
+            amount_in,
+            slippage_tolerance,
+            expires_at,
+        )
+    }
+
+    /// Place a trailing stop whose trigger follows the best EMA price seen
+    /// since placement
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `order_id` - Caller-chosen id, unique per user and pool
+    /// * `order_side` - Buy (trails the high) or Sell (trails the low)
+    /// * `amount_in` - Input token amount
+    /// * `trailing_offset` - Fixed trigger offset from the best price (scaled by 1e9), or 0
+    /// * `trailing_offset_bps` - Trigger offset in basis points of the best price, or 0
+    /// * `slippage_tolerance` - Slippage tolerance against the EMA price in basis points
+    /// * `expires_at` - Expiration timestamp (0 = never expires)
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_trailing_stop_order(
+        ctx: Context<PlaceTrailingStopOrder>,
+        order_id: u64,
+        order_side: OrderSide,
+        amount_in: u64,
+        trailing_offset: u64,
+        trailing_offset_bps: u16,
+        slippage_tolerance: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_trailing_stop_order::handler(
+            ctx,
+            order_id,
+            order_side,
+            amount_in,
+            trailing_offset,
+            trailing_offset_bps,
+            slippage_tolerance,
+            expires_at,
+        )
+    }
+
+    /// Ratchet a trailing stop to a better EMA price (keeper)
+    pub fn update_trailing_stop(ctx: Context<UpdateTrailingStop>) -> Result<()> {
+        instructions::update_trailing_stop::handler(ctx)
+    }
+
+    /// Execute a stop or trailing stop order once the pool EMA price crosses its trigger (keeper)
+    pub fn execute_stop_order(ctx: Context<ExecuteStopOrder>) -> Result<()> {
+        instructions::execute_stop_order::handler(ctx)
+    }
+
+    /// Cancel a stop order and return escrowed funds
+    pub fn cancel_stop_order(ctx: Context<CancelStopOrder>) -> Result<()> {
+        instructions::cancel_stop_order::handler(ctx)
+    }
+
+    /// Place a one-cancels-other order: a take-profit limit leg and a stop leg
+    /// sharing one escrow
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `order_id` - Caller-chosen id, unique per user and pool
+    /// * `order_side` - Buy or Sell (both legs)
+    /// * `amount_in` - Input token amount
+    /// * `take_profit_price` - Take-profit limit price (token B per token A, scaled by 1e9)
+    /// * `stop_trigger_price` - Stop trigger on the EMA price (scaled by 1e9)
+    /// * `stop_limit_price` - Worst stop fill price (0 = stop-market)
+    /// * `slippage_tolerance` - Stop-market slippage against the EMA price in basis points
+    /// * `expires_at` - Expiration timestamp (0 = never expires)
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_oco_order(
+        ctx: Context<PlaceOcoOrder>,
+        order_id: u64,
+        order_side: OrderSide,
+        amount_in: u64,
+        take_profit_price: u64,
+        stop_trigger_price: u64,
+        stop_limit_price: u64,
+        slippage_tolerance: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_oco_order::handler(
+            ctx,
+            order_id,
+            order_side,
+            amount_in,
+            take_profit_price,
+            stop_trigger_price,
+            stop_limit_price,
+            slippage_tolerance,
+            expires_at,
+        )
+    }
+
+    /// Enter at market and attach a take-profit / stop-loss OCO exit on the
+    /// entry output
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `order_id` - Caller-chosen id, unique per user and pool
+    /// * `entry_side` - Buy or Sell for the entry; the exit legs take the opposite side
+    /// * `amount_in` - Entry input token amount
+    /// * `minimum_amount_out` - Minimum entry output (slippage protection)
+    /// * `take_profit_price` - Exit take-profit limit price (scaled by 1e9)
+    /// * `stop_trigger_price` - Exit stop trigger on the EMA price (scaled by 1e9)
+    /// * `stop_limit_price` - Worst exit stop fill price (0 = stop-market)
+    /// * `slippage_tolerance` - Stop-market slippage against the EMA price in basis points
+    /// * `expires_at` - Exit expiration timestamp (0 = never expires)
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_bracket_order(
+        ctx: Context<PlaceBracketOrder>,
+        order_id: u64,
+        entry_side: OrderSide,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        take_profit_price: u64,
+        stop_trigger_price: u64,
+        stop_limit_price: u64,
+        slippage_tolerance: u16,
+        expires_at: i64,
+    ) -> Result<()> {
+        instructions::place_bracket_order::handler(
+            ctx,
+            order_id,
+            entry_side,
+            amount_in,
+            minimum_amount_out,
+            take_profit_price,
+            stop_trigger_price,
+            stop_limit_price,
+            slippage_tolerance,
+            expires_at,
+        )
+    }
+
+    /// Execute one leg of an OCO order, ending the other (keeper)
+    pub fn execute_oco_order(ctx: Context<ExecuteOcoOrder>, leg: OcoLeg) -> Result<()> {
+        instructions::execute_oco_order::handler(ctx, leg)
+    }
+
+    /// Cancel an open OCO order (or close a filled one) and return escrowed funds
+    pub fn cancel_oco_order(ctx: Context<CancelOcoOrder>) -> Result<()> {
+        instructions::cancel_oco_order::handler(ctx)
+    }
+
+    /// Initialize a user's signed order nonce (payable by anyone, e.g. a relayer)
+    pub fn initialize_signed_order_nonce(ctx: Context<InitializeSignedOrderNonce>) -> Result<()> {
+        instructions::initialize_signed_order_nonce::handler(ctx)
+    }
+
+    /// Settle an order signed off-chain by its owner (relayer)
+    /// 
+    /// The transaction must place an ed25519 program instruction verifying
+    /// the owner's signature over `order.message()` right before this one.
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `order` - The signed order
+    pub fn fill_signed_order(ctx: Context<FillSignedOrder>, order: SignedOrder) -> Result<()> {
+        instructions::fill_signed_order::handler(ctx, order)
+    }
+
+    /// Invalidate every outstanding signed order with a nonce below `next_nonce`
+    pub fn cancel_signed_orders(ctx: Context<CancelSignedOrders>, next_nonce: u64) -> Result<()> {
+        instructions::cancel_signed_orders::handler(ctx, next_nonce)
+    }
+
+    /// Settle a firm quote signed by a whitelisted market maker (taker)
+    /// 
+    /// The transaction must place an ed25519 program instruction verifying
+    /// the maker's signature over `quote.message()` right before this one.
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `quote` - The signed quote
+    pub fn fill_rfq_quote(ctx: Context<FillRfqQuote>, quote: RfqQuote) -> Result<()> {
+        instructions::fill_rfq_quote::handler(ctx, quote)
+    }
+
     /// Execute a token swap with slippage protection (legacy)
     /// 
     /// # Arguments
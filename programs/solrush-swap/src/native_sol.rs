@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer as SystemTransfer};
+use anchor_spl::token::{self, CloseAccount, SyncNative};
+
+/// Move lamports from `payer` into a wrapped SOL token account and sync its balance
+pub fn wrap_sol<'info>(
+    payer: &AccountInfo<'info>,
+    wsol_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            SystemTransfer {
+                from: payer.clone(),
+                to: wsol_account.clone(),
+            },
+        ),
+        amount,
+    )?;
+
+    token::sync_native(CpiContext::new(
+        token_program.clone(),
+        SyncNative {
+            account: wsol_account.clone(),
+        },
+    ))
+}
+
+/// Close a wrapped SOL token account, sending its balance and rent to `destination` as lamports
+pub fn unwrap_sol<'info>(
+    wsol_account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token::close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        CloseAccount {
+            account: wsol_account.clone(),
+            destination: destination.clone(),
+            authority: authority.clone(),
+        },
+        signer_seeds,
+    ))
+}
@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use crate::errors::SwapError;
+use crate::state::LiquidityPool;
+use crate::price::{pool_spot_price, PRICE_PRECISION};
+
+pub use solrush_oracle::{
+    PythPrice as OraclePrice, MAX_CLOCK_SKEW, PYTH_ACCOUNT_TYPE_PRICE, PYTH_MAGIC, PYTH_STATUS_TRADING,
+};
+use solrush_oracle::PythError;
+
+/// Maximum age of an oracle price in seconds
+pub const ORACLE_MAX_STALENESS: i64 = 60;
+
+/// Maximum oracle confidence interval relative to price (2%)
+pub const ORACLE_MAX_CONFIDENCE: u64 = 200;
+
+impl From<PythError> for SwapError {
+    fn from(error: PythError) -> Self {
+        match error {
+            PythError::InvalidAccount => SwapError::InvalidOracleAccount,
+            PythError::Stale => SwapError::StaleOraclePrice,
+            PythError::FuturePublishTime => SwapError::FutureOraclePrice,
+        }
+    }
+}
+
+/// Parse the aggregate price from Pyth v2 price account data
+pub fn parse_pyth_price(data: &[u8]) -> Result<OraclePrice> {
+    solrush_oracle::parse_pyth_price(data).map_err(|error| SwapError::from(error).into())
+}
+
+/// Reject prices that are not trading, stale, published ahead of the clock,
+/// or too uncertain
+pub fn validate_oracle_price(oracle_price: &OraclePrice, now: i64) -> Result<()> {
+    require!(
+        oracle_price.is_trading() && oracle_price.price > 0,
+        SwapError::InvalidOracleAccount
+    );
+    solrush_oracle::check_publish_time(oracle_price.publish_time, now, ORACLE_MAX_STALENESS)
+        .map_err(SwapError::from)?;
+
+    let confidence = (oracle_price.conf as u128)
+        .checked_mul(10000)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_div(oracle_price.price as u128)
+        .ok_or(SwapError::MathError)?;
+    require!(
+        confidence <= ORACLE_MAX_CONFIDENCE as u128,
+        SwapError::OracleConfidenceTooWide
+    );
+
+    Ok(())
+}
+
+/// Convert an oracle price (token B per token A in whole units) to the
+/// quoted price convention (scaled by 1e9)
+pub fn oracle_price_to_pool_price(oracle_price: &OraclePrice) -> Result<u64> {
+    require!(oracle_price.price > 0, SwapError::InvalidOracleAccount);
+
+    // price * 10^expo * 1e9
+    let scale = 10u128
+        .checked_pow(oracle_price.expo.unsigned_abs())
+        .ok_or(SwapError::CalculationOverflow)?;
+
+    let price = if oracle_price.expo >= 0 {
+        (oracle_price.price as u128)
+            .checked_mul(PRICE_PRECISION)
+            .and_then(|price| price.checked_mul(scale))
+            .ok_or(SwapError::CalculationOverflow)?
+    } else {
+        (oracle_price.price as u128)
+            .checked_mul(PRICE_PRECISION)
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(scale)
+            .ok_or(SwapError::MathError)?
+    };
+
+    u64::try_from(price).map_err(|_| SwapError::CalculationOverflow.into())
+}
+
+/// Deviation between two prices in basis points of the reference price
+pub fn calculate_price_deviation(price: u64, reference_price: u64) -> Result<u64> {
+    require!(reference_price > 0, SwapError::InvalidOracleAccount);
+
+    let deviation = (price.abs_diff(reference_price) as u128)
+        .checked_mul(10000)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_div(reference_price as u128)
+        .ok_or(SwapError::MathError)?;
+
+    Ok(deviation.min(u64::MAX as u128) as u64)
+}
+
+/// Reject execution when the pool spot price is outside the configured band
+/// around the oracle price. Pools without an oracle skip the check; pools
+/// with one require the matching account to be passed.
+pub fn check_oracle_deviation(
+    pool: &LiquidityPool,
+    oracle: Option<&AccountInfo>,
+    now: i64,
+) -> Result<()> {
+    if pool.oracle == Pubkey::default() {
+        return Ok(());
+    }
+
+    let oracle = oracle.ok_or(SwapError::InvalidOracleAccount)?;
+    require_keys_eq!(oracle.key(), pool.oracle, SwapError::InvalidOracleAccount);
+
+    let oracle_price = parse_pyth_price(&oracle.try_borrow_data()?)?;
+    validate_oracle_price(&oracle_price, now)?;
+
+    let reference_price = oracle_price_to_pool_price(&oracle_price)?;
+    let spot_price = pool_spot_price(pool)?;
+
+    require!(
+        calculate_price_deviation(spot_price, reference_price)? <= pool.max_oracle_deviation as u64,
+        SwapError::OraclePriceDeviation
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price::PriceScale;
+    use solrush_oracle::mock_pyth_account;
+
+    fn sol_usdc_pool(oracle: Pubkey, reserve_a: u64, reserve_b: u64) -> LiquidityPool {
+        LiquidityPool {
+            authority: Pubkey::default(),
+            trading_pair: crate::state::TradingPair::SolUsdc,
+            token_a_mint: Pubkey::default(),
+            token_b_mint: Pubkey::default(),
+            token_a_decimals: 9,
+            token_b_decimals: 6,
+            token_a_vault: Pubkey::default(),
+            token_b_vault: Pubkey::default(),
+            lp_token_mint: Pubkey::default(),
+            reserve_a,
+            reserve_b,
+            lp_supply: 0,
+            fee_rate: 30,
+            total_fees_a: 0,
+            total_fees_b: 0,
+            last_update_timestamp: 0,
+            ema_price: 0,
+            ema_last_update: 0,
+            oracle,
+            max_oracle_deviation: 100, // 1%
+            trade_history: Pubkey::default(),
+            is_paused: false,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_pyth_price() {
+        let data = mock_pyth_account(150_00000000, 5_000000, -8, 1_000);
+        let oracle_price = parse_pyth_price(&data).unwrap();
+        assert_eq!(oracle_price.price, 150_00000000);
+        assert_eq!(oracle_price.conf, 5_000000);
+        assert_eq!(oracle_price.expo, -8);
+        assert_eq!(oracle_price.publish_time, 1_000);
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = 0;
+        assert!(parse_pyth_price(&bad_magic).is_err());
+        assert!(parse_pyth_price(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn test_validate_oracle_price() {
+        let fresh = parse_pyth_price(&mock_pyth_account(150_00000000, 1_00000000, -8, 1_000)).unwrap();
+        assert!(validate_oracle_price(&fresh, 1_000 + ORACLE_MAX_STALENESS).is_ok());
+        assert!(validate_oracle_price(&fresh, 1_001 + ORACLE_MAX_STALENESS).is_err());
+
+        // A publish time past the clock skew allowance is not treated as fresh
+        assert!(validate_oracle_price(&fresh, 1_000 - MAX_CLOCK_SKEW).is_ok());
+        assert!(validate_oracle_price(&fresh, 999 - MAX_CLOCK_SKEW).is_err());
+
+        // 5 / 150 = 3.3% confidence is too wide
+        let uncertain = parse_pyth_price(&mock_pyth_account(150_00000000, 5_00000000, -8, 1_000)).unwrap();
+        assert!(validate_oracle_price(&uncertain, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_oracle_price_to_pool_price() {
+        // 150 USDC per SOL, scaled by 1e9
+        let oracle_price = parse_pyth_price(&mock_pyth_account(150_00000000, 0, -8, 0)).unwrap();
+        assert_eq!(oracle_price_to_pool_price(&oracle_price).unwrap(), 150_000_000_000);
+        assert_eq!(
+            oracle_price_to_pool_price(&oracle_price).unwrap(),
+            PriceScale::new(9, 6).spot_price(1_000_000_000, 150_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_check_oracle_deviation() {
+        let oracle_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0u64;
+        let mut data = mock_pyth_account(150_00000000, 10_000000, -8, 1_000);
+        let oracle = AccountInfo::new(&oracle_key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        // 1000 SOL / 150_000 USDC matches the oracle
+        let pool = sol_usdc_pool(oracle_key, 1_000_000_000_000, 150_000_000_000);
+        assert!(check_oracle_deviation(&pool, Some(&oracle), 1_000).is_ok());
+
+        // Pool pushed 5% above the oracle is rejected
+        let pushed = sol_usdc_pool(oracle_key, 1_000_000_000_000, 157_500_000_000);
+        assert!(check_oracle_deviation(&pushed, Some(&oracle), 1_000).is_err());
+
+        // Missing or mismatched oracle account is rejected
+        assert!(check_oracle_deviation(&pool, None, 1_000).is_err());
+        let other = sol_usdc_pool(Pubkey::new_unique(), 1_000_000_000_000, 150_000_000_000);
+        assert!(check_oracle_deviation(&other, Some(&oracle), 1_000).is_err());
+
+        // Pools without an oracle skip the check
+        let unchecked = sol_usdc_pool(Pubkey::default(), 1_000_000_000_000, 157_500_000_000);
+        assert!(check_oracle_deviation(&unchecked, None, 1_000).is_ok());
+    }
+}
@@ -0,0 +1,188 @@
+use anchor_lang::prelude::*;
+use crate::errors::SwapError;
+use crate::state::{LiquidityPool, OrderSide};
+
+/// Price scaling factor (1e9)
+pub const PRICE_PRECISION: u128 = 1_000_000_000;
+
+/// Largest mint decimals difference a pool can price
+pub const MAX_DECIMALS_DIFFERENCE: u8 = 18;
+
+/// Converts between base unit amounts and quoted prices for a pool's mints
+///
+/// Every price in the program (`limit_price`, `min_price`, `max_price`,
+/// stop and OCO prices, the EMA and recorded trade prices) is quoted as
+/// whole token B per whole token A, scaled by 1e9, regardless of order side.
+/// At 150 USDC per SOL the price is 150_000_000_000 for buys and sells alike.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceScale {
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+}
+
+impl PriceScale {
+    pub fn new(token_a_decimals: u8, token_b_decimals: u8) -> Self {
+        Self { token_a_decimals, token_b_decimals }
+    }
+
+    pub fn of(pool: &LiquidityPool) -> Self {
+        Self::new(pool.token_a_decimals, pool.token_b_decimals)
+    }
+
+    /// `(num, den)` such that quoted price = base unit ratio * 1e9 * num / den
+    fn decimals_factors(&self) -> Result<(u128, u128)> {
+        let difference = self.token_a_decimals.abs_diff(self.token_b_decimals);
+        require!(difference <= MAX_DECIMALS_DIFFERENCE, SwapError::CalculationOverflow);
+
+        let factor = 10u128.pow(difference as u32);
+        Ok(if self.token_a_decimals >= self.token_b_decimals {
+            (factor, 1)
+        } else {
+            (1, factor)
+        })
+    }
+
+    /// Quoted price of trading `amount_a` against `amount_b` (base units)
+    pub fn price(&self, amount_a: u64, amount_b: u64) -> Result<u64> {
+        require!(amount_a > 0 && amount_b > 0, SwapError::InsufficientLiquidity);
+        let (num, den) = self.decimals_factors()?;
+
+        let price = (amount_b as u128)
+            .checked_mul(PRICE_PRECISION)
+            .and_then(|value| value.checked_mul(num))
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(
+                (amount_a as u128)
+                    .checked_mul(den)
+                    .ok_or(SwapError::CalculationOverflow)?,
+            )
+            .ok_or(SwapError::MathError)?;
+
+        u64::try_from(price).map_err(|_| SwapError::CalculationOverflow.into())
+    }
+
+    /// Spot price of the pool reserves
+    pub fn spot_price(&self, reserve_a: u64, reserve_b: u64) -> Result<u64> {
+        self.price(reserve_a, reserve_b)
+    }
+
+    /// Token B base units worth `amount_a` token A base units at `price`
+    pub fn amount_b_at_price(&self, amount_a: u64, price: u64) -> Result<u64> {
+        require!(price > 0, SwapError::InvalidLimitPrice);
+        let (num, den) = self.decimals_factors()?;
+
+        let amount_b = (amount_a as u128)
+            .checked_mul(price as u128)
+            .and_then(|value| value.checked_mul(den))
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(PRICE_PRECISION * num)
+            .ok_or(SwapError::MathError)?;
+
+        u64::try_from(amount_b).map_err(|_| SwapError::CalculationOverflow.into())
+    }
+
+    /// Token A base units worth `amount_b` token B base units at `price`
+    pub fn amount_a_at_price(&self, amount_b: u64, price: u64) -> Result<u64> {
+        require!(price > 0, SwapError::InvalidLimitPrice);
+        let (num, den) = self.decimals_factors()?;
+
+        let amount_a = (amount_b as u128)
+            .checked_mul(PRICE_PRECISION)
+            .and_then(|value| value.checked_mul(num))
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(
+                (price as u128)
+                    .checked_mul(den)
+                    .ok_or(SwapError::CalculationOverflow)?,
+            )
+            .ok_or(SwapError::MathError)?;
+
+        u64::try_from(amount_a).map_err(|_| SwapError::CalculationOverflow.into())
+    }
+
+    /// Output of an order spending `amount_in` at `price`
+    ///
+    /// Buy orders spend token A and receive token B; Sell orders spend token B
+    /// and receive token A.
+    pub fn output_at_price(&self, amount_in: u64, price: u64, order_side: OrderSide) -> Result<u64> {
+        match order_side {
+            OrderSide::Buy => self.amount_b_at_price(amount_in, price),
+            OrderSide::Sell => self.amount_a_at_price(amount_in, price),
+        }
+    }
+
+    /// Average quoted price of a fill
+    pub fn execution_price(&self, amount_in: u64, amount_out: u64, order_side: OrderSide) -> Result<u64> {
+        match order_side {
+            OrderSide::Buy => self.price(amount_in, amount_out),
+            OrderSide::Sell => self.price(amount_out, amount_in),
+        }
+    }
+}
+
+/// Quoted spot price of a pool
+pub fn pool_spot_price(pool: &LiquidityPool) -> Result<u64> {
+    PriceScale::of(pool).spot_price(pool.reserve_a, pool.reserve_b)
+}
+
+/// Whether a fill at `price` is at or better than `limit_price`
+///
+/// Buys spend token A and want more token B for it, so they fill at or above
+/// the limit; sells spend token B and fill at or below it.
+pub fn is_price_at_or_better(price: u64, order_side: OrderSide, limit_price: u64) -> bool {
+    match order_side {
+        OrderSide::Buy => price >= limit_price,
+        OrderSide::Sell => price <= limit_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_USDC: PriceScale = PriceScale { token_a_decimals: 9, token_b_decimals: 6 };
+
+    #[test]
+    fn test_price_is_decimal_aware() {
+        // 1 SOL = 150 USDC
+        let price = SOL_USDC.price(1_000_000_000, 150_000_000).unwrap();
+        assert_eq!(price, 150 * PRICE_PRECISION as u64);
+
+        // Token B with more decimals than token A
+        let scale = PriceScale::new(6, 9);
+        assert_eq!(scale.price(1_000_000, 2_000_000_000).unwrap(), 2 * PRICE_PRECISION as u64);
+
+        // Reserves far beyond the old u64 intermediate limit
+        let price = SOL_USDC.price(500_000_000_000_000_000, 75_000_000_000_000_000).unwrap();
+        assert_eq!(price, 150 * PRICE_PRECISION as u64);
+
+        assert!(SOL_USDC.price(0, 1).is_err());
+        assert!(PriceScale::new(0, 19).price(1, 1).is_err());
+    }
+
+    #[test]
+    fn test_output_at_price() {
+        let price = 150 * PRICE_PRECISION as u64;
+
+        // 2 SOL -> 300 USDC, 300 USDC -> 2 SOL
+        assert_eq!(SOL_USDC.output_at_price(2_000_000_000, price, OrderSide::Buy).unwrap(), 300_000_000);
+        assert_eq!(SOL_USDC.output_at_price(300_000_000, price, OrderSide::Sell).unwrap(), 2_000_000_000);
+        assert!(SOL_USDC.output_at_price(100, 0, OrderSide::Sell).is_err());
+
+        // Execution price is quoted the same way for both sides
+        assert_eq!(SOL_USDC.execution_price(2_000_000_000, 300_000_000, OrderSide::Buy).unwrap(), price);
+        assert_eq!(SOL_USDC.execution_price(300_000_000, 2_000_000_000, OrderSide::Sell).unwrap(), price);
+    }
+
+    #[test]
+    fn test_is_price_at_or_better() {
+        let limit = 150 * PRICE_PRECISION as u64;
+
+        assert!(is_price_at_or_better(limit + 1, OrderSide::Buy, limit));
+        assert!(is_price_at_or_better(limit, OrderSide::Buy, limit));
+        assert!(!is_price_at_or_better(limit - 1, OrderSide::Buy, limit));
+
+        assert!(is_price_at_or_better(limit - 1, OrderSide::Sell, limit));
+        assert!(!is_price_at_or_better(limit + 1, OrderSide::Sell, limit));
+    }
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::errors::SwapError;
+use crate::fees::load_if_initialized;
 
 /// Trading pair enum
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -24,6 +26,10 @@ pub enum OrderType {
     Market,
     Limit,
     DCA,
+    StopMarket,
+    StopLimit,
+    Oco,
+    TrailingStop,
 }
 
 /// Order side enum
@@ -43,6 +49,30 @@ pub enum OrderStatus {
     Expired,
 }
 
+/// Time in force for swap orders
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    GoodTilCancelled,  // Resting until filled or cancelled (expires_at = 0)
+    GoodTilTime,       // Resting until expires_at
+    ImmediateOrCancel, // Fill what is possible now, refund the rest
+    FillOrKill,        // Fill everything now or fail
+    PostOnly,          // Resting only, rejected if it would execute on placement
+}
+
+/// Trigger condition for stop orders, evaluated against the pool EMA price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerCondition {
+    AtOrBelow, // Stop-loss: fires when price falls to the trigger
+    AtOrAbove, // Take-profit: fires when price rises to the trigger
+}
+
+/// Leg of a one-cancels-other order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OcoLeg {
+    TakeProfit, // Limit leg at the take-profit price
+    StopLoss,   // Stop leg triggered by the pool EMA price
+}
+
 /// Liquidity pool configuration for AMM
 #[account]
 pub struct LiquidityPool {
@@ -62,6 +92,14 @@ pub struct LiquidityPool {
     pub last_update_timestamp: i64,
     pub is_paused: bool,
     pub bump: u8,
+    // Fields below were appended after launch; older pools go through `migrate_pool`
+    pub ema_price: u64,            // Time-weighted EMA of the quoted price (see price.rs)
+    pub ema_last_update: i64,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+    pub oracle: Pubkey,            // Pyth-format price account (default = no oracle check)
+    pub max_oracle_deviation: u16, // Max spot/oracle deviation in basis points
+    pub trade_history: Pubkey,     // Trade history ring buffer (default = none); required on fills once set
 }
 
 impl LiquidityPool {
@@ -81,7 +119,38 @@ impl LiquidityPool {
         8 +  // total_fees_b
         8 +  // last_update_timestamp
         1 +  // is_paused
-        1;   // bump
+        1 +  // bump
+        8 +  // ema_price
+        8 +  // ema_last_update
+        1 +  // token_a_decimals
+        1 +  // token_b_decimals
+        32 + // oracle
+        2 +  // max_oracle_deviation
+        32;  // trade_history
+
+    /// Size of pools created before the appended fields
+    pub const LEGACY_LEN: usize = 8 + 32 + 1 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 2 + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Pool layout before the appended fields, read by `migrate_pool`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyLiquidityPool {
+    pub authority: Pubkey,
+    pub trading_pair: TradingPair,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+    pub fee_rate: u16,
+    pub total_fees_a: u64,
+    pub total_fees_b: u64,
+    pub last_update_timestamp: i64,
+    pub is_paused: bool,
+    pub bump: u8,
 }
 
 /// Order book for limit orders
@@ -94,6 +163,7 @@ pub struct OrderBook {
     pub sell_orders_count: u64,
     pub total_volume: u64,
     pub bump: u8,
+    pub next_order_id: u64,        // Next limit order index; only ever incremented
 }
 
 impl OrderBook {
@@ -104,7 +174,23 @@ impl OrderBook {
         8 +  // buy_orders_count
         8 +  // sell_orders_count
         8 +  // total_volume
-        1;   // bump
+        1 +  // bump
+        8;   // next_order_id
+
+    /// Size of order books created before `next_order_id`
+    pub const LEGACY_LEN: usize = 8 + 32 + 1 + 32 + 8 + 8 + 8 + 1;
+}
+
+/// Order book layout before `next_order_id`, read by `migrate_order_book`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyOrderBook {
+    pub authority: Pubkey,
+    pub trading_pair: TradingPair,
+    pub pool: Pubkey,
+    pub buy_orders_count: u64,
+    pub sell_orders_count: u64,
+    pub total_volume: u64,
+    pub bump: u8,
 }
 
 /// Limit order account
@@ -121,7 +207,9 @@ pub struct LimitOrder {
     pub slippage_tolerance: u16,   // Basis points
     pub created_at: i64,
     pub expires_at: i64,           // 0 = never expires
-    pub user_token_account: Pubkey,
+    pub time_in_force: TimeInForce,
+    pub order_index: u64,          // Order book index used in the PDA seeds
+    pub user_token_account: Pubkey, // Default when funded with native SOL
     pub escrow_token_account: Pubkey,
     pub bump: u8,
 }
@@ -139,11 +227,114 @@ impl LimitOrder {
         2 +  // slippage_tolerance
         8 +  // created_at
         8 +  // expires_at
+        1 +  // time_in_force
+        8 +  // order_index
+        32 + // user_token_account
+        32 + // escrow_token_account
+        1;   // bump
+}
+
+/// Stop order account (stop-market, stop-limit or trailing stop)
+#[account]
+pub struct StopOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub trading_pair: TradingPair,
+    pub order_type: OrderType,     // StopMarket, StopLimit or TrailingStop
+    pub order_side: OrderSide,
+    pub order_status: OrderStatus,
+    pub trigger_condition: TriggerCondition,
+    pub trigger_price: u64,        // Token B per token A (scaled by 1e9)
+    pub limit_price: u64,          // Worst fill price for StopLimit (0 otherwise)
+    pub trailing_offset: u64,      // TrailingStop fixed offset from best_price (scaled by 1e9)
+    pub trailing_offset_bps: u16,  // TrailingStop offset in basis points of best_price
+    pub best_price: u64,           // TrailingStop best EMA price seen since placement
+    pub amount_in: u64,
+    pub slippage_tolerance: u16,   // Basis points, applied against the EMA price
+    pub order_id: u64,             // Caller-chosen id used in the PDA seeds
+    pub created_at: i64,
+    pub expires_at: i64,           // 0 = never expires
+    pub user_token_account: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub bump: u8,
+}
+
+impl StopOrder {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        32 + // pool
+        1 +  // trading_pair
+        1 +  // order_type
+        1 +  // order_side
+        1 +  // order_status
+        1 +  // trigger_condition
+        8 +  // trigger_price
+        8 +  // limit_price
+        8 +  // trailing_offset
+        2 +  // trailing_offset_bps
+        8 +  // best_price
+        8 +  // amount_in
+        2 +  // slippage_tolerance
+        8 +  // order_id
+        8 +  // created_at
+        8 +  // expires_at
         32 + // user_token_account
         32 + // escrow_token_account
         1;   // bump
 }
 
+/// One-cancels-other order: a take-profit limit leg and a stop leg sharing
+/// one escrow. Filling either leg consumes the escrow and ends the other.
+#[account]
+pub struct OcoOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub trading_pair: TradingPair,
+    pub order_side: OrderSide,     // Side of both legs
+    pub order_status: OrderStatus,
+    pub take_profit_price: u64,    // Token B per token A (scaled by 1e9)
+    pub stop_trigger_price: u64,   // Token B per token A (scaled by 1e9)
+    pub stop_limit_price: u64,     // Worst stop fill price (0 = stop-market)
+    pub amount_in: u64,
+    pub slippage_tolerance: u16,   // Basis points, applied to a stop-market fill
+    pub filled_leg: Option<OcoLeg>,
+    pub order_id: u64,             // Caller-chosen id used in the PDA seeds
+    pub created_at: i64,
+    pub expires_at: i64,           // 0 = never expires
+    pub user_token_account: Pubkey,
+    pub escrow_token_account: Pubkey,
+    pub bump: u8,
+}
+
+impl OcoOrder {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        32 + // pool
+        1 +  // trading_pair
+        1 +  // order_side
+        1 +  // order_status
+        8 +  // take_profit_price
+        8 +  // stop_trigger_price
+        8 +  // stop_limit_price
+        8 +  // amount_in
+        2 +  // slippage_tolerance
+        1 + 1 + // filled_leg
+        8 +  // order_id
+        8 +  // created_at
+        8 +  // expires_at
+        32 + // user_token_account
+        32 + // escrow_token_account
+        1;   // bump
+
+    /// The stop leg protects against the price moving against the order side
+    pub fn stop_trigger_condition(&self) -> TriggerCondition {
+        match self.order_side {
+            OrderSide::Buy => TriggerCondition::AtOrBelow,
+            OrderSide::Sell => TriggerCondition::AtOrAbove,
+        }
+    }
+}
+
 /// DCA (Dollar Cost Averaging) order account
 #[account]
 pub struct DCAOrder {
@@ -158,12 +349,13 @@ pub struct DCAOrder {
     pub last_execution: i64,       // Last execution timestamp
     pub next_execution: i64,       // Next scheduled execution
     pub slippage_tolerance: u16,   // Basis points
-    pub min_price: u64,            // Min acceptable price (0 = no limit)
-    pub max_price: u64,            // Max acceptable price (0 = no limit)
+    pub min_price: u64,            // Min pool price, token B per token A (0 = no limit)
+    pub max_price: u64,            // Max pool price, token B per token A (0 = no limit)
     pub user_token_account: Pubkey,
     pub escrow_token_account: Pubkey,
     pub total_amount_in: u64,      // Total deposited
     pub total_amount_out: u64,     // Total received
+    pub order_id: u64,             // Caller-chosen id used in the PDA seeds
     pub bump: u8,
 }
 
@@ -186,34 +378,241 @@ impl DCAOrder {
         32 + // escrow_token_account
         8 +  // total_amount_in
         8 +  // total_amount_out
+        8 +  // order_id
         1;   // bump
 }
 
-/// Market order execution record
+/// Maximum open orders tracked per user; keeps the index in one small account
+pub const MAX_USER_ORDERS: usize = 32;
+
+/// Open order entry in a user's order index
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct UserOrderEntry {
+    pub order: Pubkey,
+    pub order_type: OrderType,
+}
+
+/// Optional per-user index of open order accounts
+///
+/// Best effort: orders placed while it is full, or placed or settled without
+/// passing it, are not reflected. The order accounts themselves, found by
+/// owner, are authoritative.
 #[account]
-pub struct MarketOrderRecord {
+pub struct UserOrders {
     pub owner: Pubkey,
-    pub trading_pair: TradingPair,
+    pub orders: Vec<UserOrderEntry>,
+    pub bump: u8,
+}
+
+impl UserOrders {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        4 + MAX_USER_ORDERS * (32 + 1) + // orders
+        1;   // bump
+
+    /// Index an order; a full index must be pruned before placing more
+    pub fn add_order(&mut self, order: Pubkey, order_type: OrderType) -> Result<()> {
+        require!(self.orders.len() < MAX_USER_ORDERS, SwapError::UserOrdersFull);
+        self.orders.push(UserOrderEntry { order, order_type });
+        Ok(())
+    }
+
+    pub fn remove_order(&mut self, order: &Pubkey) {
+        self.orders.retain(|entry| entry.order != *order);
+    }
+
+    /// Drop `order` from the index at `account`, if the owner has created one
+    ///
+    /// Keeper instructions take the index at its fixed address this way, so
+    /// a keeper cannot leave it out and strand the entry.
+    pub fn remove_order_at(account: &AccountInfo, order: &Pubkey) -> Result<()> {
+        if let Some(mut user_orders) = load_if_initialized::<UserOrders>(account)? {
+            user_orders.remove_order(order);
+            user_orders.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        }
+        Ok(())
+    }
+}
+
+/// Domain prefix of the message a user signs for an off-chain order
+pub const SIGNED_ORDER_DOMAIN: &[u8] = b"solrush-swap:signed-order:v1";
+
+/// Order intent signed off-chain by its owner and settled by a relayer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SignedOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
     pub order_side: OrderSide,
     pub amount_in: u64,
-    pub amount_out: u64,
-    pub fee_paid: u64,
-    pub price_impact: u16,
-    pub executed_at: i64,
-    pub tx_signature: [u8; 64],
+    pub minimum_amount_out: u64,
+    pub relayer_fee: u64,          // Paid to the relayer in the input token
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+impl SignedOrder {
+    /// Bytes the owner signs with ed25519
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = SIGNED_ORDER_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Per-user replay protection for signed orders
+#[account]
+pub struct SignedOrderNonce {
+    pub owner: Pubkey,
+    pub next_nonce: u64,           // Lowest nonce still accepted
+    pub bump: u8,
 }
 
-impl MarketOrderRecord {
+impl SignedOrderNonce {
     pub const LEN: usize = 8 + // discriminator
         32 + // owner
-        1 +  // trading_pair
-        1 +  // order_side
-        8 +  // amount_in
-        8 +  // amount_out
-        8 +  // fee_paid
-        2 +  // price_impact
-        8 +  // executed_at
-        64;  // tx_signature
+        8 +  // next_nonce
+        1;   // bump
+}
+
+/// Domain prefix of the message a market maker signs for an RFQ quote
+pub const RFQ_QUOTE_DOMAIN: &[u8] = b"solrush-swap:rfq-quote:v1";
+
+/// Firm quote signed off-chain by a whitelisted market maker
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RfqQuote {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub pool: Pubkey,              // Pool of the quoted trading pair
+    pub order_side: OrderSide,     // Taker side
+    pub amount_in: u64,            // Paid by the taker
+    pub amount_out: u64,           // Paid by the maker
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+impl RfqQuote {
+    /// Bytes the maker signs with ed25519
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = RFQ_QUOTE_DOMAIN.to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
+
+/// Marker of a settled RFQ quote; its existence blocks replay
+#[account]
+pub struct RfqFill {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub nonce: u64,
+    pub filled_at: i64,
+    pub bump: u8,
+}
+
+impl RfqFill {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // maker
+        32 + // taker
+        8 +  // nonce
+        8 +  // filled_at
+        1;   // bump
+}
+
+/// Days in the rolling trading volume window used for fee tiers
+pub const VOLUME_WINDOW_DAYS: usize = 30;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Per-user rolling 30-day trading volume, in token A (SOL) base units
+#[account]
+pub struct UserVolume {
+    pub owner: Pubkey,
+    pub daily_volume: [u64; VOLUME_WINDOW_DAYS], // Indexed by day % VOLUME_WINDOW_DAYS
+    pub last_day: i64,             // Day number (unix time / 86400) of the newest bucket
+    pub bump: u8,
+}
+
+impl UserVolume {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        VOLUME_WINDOW_DAYS * 8 + // daily_volume
+        8 +  // last_day
+        1;   // bump
+
+    fn bucket(day: i64) -> usize {
+        day.rem_euclid(VOLUME_WINDOW_DAYS as i64) as usize
+    }
+
+    /// Add a trade to today's bucket, clearing days that left the window
+    pub fn record(&mut self, now: i64, amount: u64) -> Result<()> {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+
+        if today > self.last_day {
+            let stale_days = (today - self.last_day).min(VOLUME_WINDOW_DAYS as i64);
+            for day in (today - stale_days + 1)..=today {
+                self.daily_volume[Self::bucket(day)] = 0;
+            }
+            self.last_day = today;
+        }
+
+        let bucket = &mut self.daily_volume[Self::bucket(self.last_day)];
+        *bucket = bucket.checked_add(amount).ok_or(SwapError::CalculationOverflow)?;
+        Ok(())
+    }
+
+    /// Volume over the last 30 days including today
+    pub fn rolling_volume(&self, now: i64) -> u64 {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        let first_day = today - VOLUME_WINDOW_DAYS as i64 + 1;
+
+        (first_day.max(self.last_day - VOLUME_WINDOW_DAYS as i64 + 1)..=self.last_day.min(today))
+            .map(|day| self.daily_volume[Self::bucket(day)])
+            .fold(0u64, |total, volume| total.saturating_add(volume))
+    }
+}
+
+/// Number of trades kept in a pool's trade history
+pub const TRADE_HISTORY_CAPACITY: usize = 64;
+
+/// Trade entry in a pool's trade history
+#[zero_copy]
+pub struct TradeRecord {
+    pub trader: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub price: u64,                // Execution price, token B per token A (scaled by 1e9)
+    pub timestamp: i64,
+    pub order_type: u8,            // OrderType as u8
+    pub order_side: u8,            // OrderSide as u8
+    pub padding: [u8; 6],
+}
+
+/// Fixed-size ring buffer of a pool's most recent trades
+#[account(zero_copy)]
+pub struct TradeHistory {
+    pub pool: Pubkey,
+    pub total_trades: u64,         // Trades recorded since creation
+    pub head: u64,                 // Index of the next slot to write
+    pub trades: [TradeRecord; TRADE_HISTORY_CAPACITY],
+    pub bump: u8,
+    pub padding: [u8; 7],
+}
+
+impl TradeHistory {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // pool
+        8 +  // total_trades
+        8 +  // head
+        TRADE_HISTORY_CAPACITY * (32 + 8 + 8 + 8 + 8 + 1 + 1 + 6) + // trades
+        1 +  // bump
+        7;   // padding
+
+    pub fn push(&mut self, record: TradeRecord) {
+        let head = self.head as usize % TRADE_HISTORY_CAPACITY;
+        self.trades[head] = record;
+        self.head = ((head + 1) % TRADE_HISTORY_CAPACITY) as u64;
+        self.total_trades = self.total_trades.saturating_add(1);
+    }
 }
 
 /// Swap quote information
@@ -226,6 +625,22 @@ pub struct SwapQuote {
     pub minimum_received: u64,
 }
 
+/// Liquidity available between the spot price and one price level
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DepthLevel {
+    pub price: u64,                // Token B per token A (scaled by 1e9)
+    pub amm_amount: u64,           // Token A the AMM trades from spot to this price
+    pub order_amount: u64,         // Token A resting limit orders fill from spot to this price
+}
+
+/// Aggregated order book and AMM depth of a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OrderBookDepth {
+    pub spot_price: u64,           // Token B per token A (scaled by 1e9)
+    pub bids: Vec<DepthLevel>,     // Levels below spot: selling token A
+    pub asks: Vec<DepthLevel>,     // Levels above spot: buying token A
+}
+
 /// Swap fee information
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct SwapFeeInfo {
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::errors::SwapError;
+use crate::price::{is_price_at_or_better, pool_spot_price, PriceScale};
+use crate::state::{LiquidityPool, OrderSide, OrderType, TradeHistory, TradeRecord, TriggerCondition};
 
 /// Maximum slippage tolerance (10%)
 pub const MAX_SLIPPAGE_TOLERANCE: u16 = 1000;
@@ -7,6 +9,22 @@ pub const MAX_SLIPPAGE_TOLERANCE: u16 = 1000;
 /// Maximum price impact allowed (5%)
 pub const MAX_PRICE_IMPACT: u16 = 500;
 
+/// Time window of the pool EMA price in seconds
+pub const PRICE_EMA_WINDOW: i64 = 300;
+
+/// Maximum number of cycles in a DCA order
+pub const MAX_DCA_CYCLES: u16 = 1000;
+
+/// Minimum seconds between DCA cycles
+pub const MIN_DCA_CYCLE_FREQUENCY: i64 = 60;
+
+/// Maximum price levels returned by the order book depth view
+pub const MAX_DEPTH_LEVELS: u8 = 32;
+
+/// Lamports paid from reclaimed rent to whoever closes an expired order
+/// (covers one transaction signature fee)
+pub const EXPIRED_ORDER_CLOSE_BOUNTY: u64 = 5_000;
+
 /// Calculate swap output with fee
 pub fn calculate_swap_with_fee(
     amount_in: u64,
@@ -105,6 +123,236 @@ pub fn validate_slippage(
     Ok(())
 }
 
+/// Append a fill to the pool's trade history, if the pool keeps one
+#[allow(clippy::too_many_arguments)]
+pub fn record_trade(
+    trade_history: Option<&AccountLoader<TradeHistory>>,
+    price_scale: PriceScale,
+    trader: Pubkey,
+    order_type: OrderType,
+    order_side: OrderSide,
+    amount_in: u64,
+    amount_out: u64,
+    timestamp: i64,
+) -> Result<()> {
+    if let Some(trade_history) = trade_history {
+        trade_history.load_mut()?.push(TradeRecord {
+            trader,
+            amount_in,
+            amount_out,
+            price: price_scale.execution_price(amount_in, amount_out, order_side)?,
+            timestamp,
+            order_type: order_type as u8,
+            order_side: order_side as u8,
+            padding: [0u8; 6],
+        });
+    }
+    Ok(())
+}
+
+/// Check whether the pool spot price satisfies a limit order's price
+pub fn is_limit_price_met(pool: &LiquidityPool, order_side: OrderSide, limit_price: u64) -> Result<bool> {
+    Ok(is_price_at_or_better(pool_spot_price(pool)?, order_side, limit_price))
+}
+
+/// Largest input that can be swapped while keeping the average price at or
+/// above `minimum_amount_out / amount_in` (used for immediate-or-cancel fills)
+///
+/// From `out(x) / x >= min_out / amount_in` with the fee-adjusted constant
+/// product formula: `x <= reserve_out * amount_in / min_out - reserve_in * 10000 / (10000 - fee)`
+pub fn calculate_max_fill_input(
+    amount_in: u64,
+    minimum_amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_rate: u16,
+) -> Result<u64> {
+    if minimum_amount_out == 0 {
+        return Ok(amount_in);
+    }
+
+    let fee_factor = 10000u128
+        .checked_sub(fee_rate as u128)
+        .filter(|f| *f > 0)
+        .ok_or(SwapError::MathError)?;
+
+    let price_bound = (reserve_out as u128)
+        .checked_mul(amount_in as u128)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_div(minimum_amount_out as u128)
+        .ok_or(SwapError::MathError)?;
+
+    // Round the subtracted term up so the bound stays conservative
+    let reserve_term = (reserve_in as u128)
+        .checked_mul(10000)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_add(fee_factor - 1)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_div(fee_factor)
+        .ok_or(SwapError::MathError)?;
+
+    let max_input = price_bound.saturating_sub(reserve_term);
+
+    Ok(max_input.min(amount_in as u128) as u64)
+}
+
+/// Validate OCO leg prices: the stop must sit on the losing side of the
+/// take-profit, and a stop-limit price no better than its trigger
+///
+/// Buys receive more token B as the price rises, so their take-profit is
+/// above the stop; sells are the mirror image.
+pub fn validate_oco_prices(
+    order_side: OrderSide,
+    take_profit_price: u64,
+    stop_trigger_price: u64,
+    stop_limit_price: u64,
+) -> Result<()> {
+    require!(take_profit_price > 0, SwapError::InvalidLimitPrice);
+    require!(stop_trigger_price > 0, SwapError::InvalidTriggerPrice);
+
+    match order_side {
+        OrderSide::Buy => {
+            require!(stop_trigger_price < take_profit_price, SwapError::InvalidTriggerPrice);
+            require!(stop_limit_price <= stop_trigger_price, SwapError::InvalidLimitPrice);
+        }
+        OrderSide::Sell => {
+            require!(stop_trigger_price > take_profit_price, SwapError::InvalidTriggerPrice);
+            require!(
+                stop_limit_price == 0 || stop_limit_price >= stop_trigger_price,
+                SwapError::InvalidLimitPrice
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Trigger price of a trailing stop, `best_price` moved against the order by
+/// a fixed offset or a basis-point offset
+///
+/// Stops that fire at or below the trigger trail the highest price seen;
+/// stops that fire at or above trail the lowest.
+pub fn calculate_trailing_trigger(
+    best_price: u64,
+    trailing_offset: u64,
+    trailing_offset_bps: u16,
+    trigger_condition: TriggerCondition,
+) -> Result<u64> {
+    let offset = if trailing_offset_bps > 0 {
+        (best_price as u128)
+            .checked_mul(trailing_offset_bps as u128)
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(10000)
+            .ok_or(SwapError::MathError)? as u64
+    } else {
+        trailing_offset
+    };
+
+    let trigger = match trigger_condition {
+        TriggerCondition::AtOrBelow => best_price.checked_sub(offset).ok_or(SwapError::InvalidTriggerPrice)?,
+        TriggerCondition::AtOrAbove => best_price.checked_add(offset).ok_or(SwapError::CalculationOverflow)?,
+    };
+    require!(trigger > 0, SwapError::InvalidTriggerPrice);
+
+    Ok(trigger)
+}
+
+/// Whether `price` improves on a trailing stop's best price
+pub fn is_trailing_price_better(price: u64, best_price: u64, trigger_condition: TriggerCondition) -> bool {
+    match trigger_condition {
+        TriggerCondition::AtOrBelow => price > best_price,
+        TriggerCondition::AtOrAbove => price < best_price,
+    }
+}
+
+/// Integer square root (floor)
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Token A the AMM curve trades between the spot price and the quoted `price`
+///
+/// Below spot this is the token A a seller pays in (fee included) to push the
+/// price down to `price`; above spot it is the token A a buyer takes out to
+/// push the price up to `price`.
+pub fn calculate_amm_depth(
+    reserve_a: u64,
+    reserve_b: u64,
+    fee_rate: u16,
+    price: u64,
+    price_scale: PriceScale,
+) -> Result<u64> {
+    require!(price > 0, SwapError::InvalidLimitPrice);
+    let spot_price = price_scale.spot_price(reserve_a, reserve_b)?;
+
+    let depth = if price < spot_price {
+        // reserve_a' = sqrt(k / price), with reserve_b / price in token A base units
+        let new_reserve_a = integer_sqrt(
+            (price_scale.amount_a_at_price(reserve_b, price)? as u128)
+                .checked_mul(reserve_a as u128)
+                .ok_or(SwapError::CalculationOverflow)?,
+        );
+        let net_in = new_reserve_a.saturating_sub(reserve_a as u128);
+        let fee_factor = 10000u128
+            .checked_sub(fee_rate as u128)
+            .filter(|f| *f > 0)
+            .ok_or(SwapError::MathError)?;
+        net_in
+            .checked_mul(10000)
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(fee_factor)
+            .ok_or(SwapError::MathError)?
+    } else if price > spot_price {
+        // reserve_b' = sqrt(k * price), reserve_a' = k / reserve_b'
+        let new_reserve_b = integer_sqrt(
+            (price_scale.amount_b_at_price(reserve_a, price)? as u128)
+                .checked_mul(reserve_b as u128)
+                .ok_or(SwapError::CalculationOverflow)?,
+        );
+        require!(new_reserve_b > 0, SwapError::InsufficientLiquidity);
+        let new_reserve_a = (reserve_a as u128)
+            .checked_mul(reserve_b as u128)
+            .ok_or(SwapError::CalculationOverflow)?
+            .checked_div(new_reserve_b)
+            .ok_or(SwapError::MathError)?;
+        (reserve_a as u128).saturating_sub(new_reserve_a)
+    } else {
+        0
+    };
+
+    Ok(depth.min(u64::MAX as u128) as u64)
+}
+
+/// Validate DCA order schedule and price band
+pub fn validate_dca_parameters(
+    amount_per_cycle: u64,
+    total_cycles: u16,
+    cycle_frequency: i64,
+    min_price: u64,
+    max_price: u64,
+) -> Result<()> {
+    require!(amount_per_cycle > 0, SwapError::InvalidAmount);
+    require!(total_cycles > 0 && total_cycles <= MAX_DCA_CYCLES, SwapError::MaxCyclesExceeded);
+    require!(cycle_frequency >= MIN_DCA_CYCLE_FREQUENCY, SwapError::InvalidCycleFrequency);
+
+    // Validate price range if specified
+    if min_price > 0 && max_price > 0 {
+        require!(min_price < max_price, SwapError::PriceOutOfRange);
+    }
+
+    Ok(())
+}
+
 /// Validate price impact
 pub fn validate_price_impact(price_impact: u16) -> Result<()> {
     require!(
@@ -114,9 +362,46 @@ pub fn validate_price_impact(price_impact: u16) -> Result<()> {
     Ok(())
 }
 
+/// Move the EMA price towards the spot price, weighted by elapsed time
+///
+/// A spot price that only lasts for the current transaction (elapsed = 0)
+/// does not move the EMA, which makes it resistant to flash manipulation.
+pub fn calculate_ema_price(ema_price: u64, spot_price: u64, elapsed: i64) -> Result<u64> {
+    if ema_price == 0 || elapsed >= PRICE_EMA_WINDOW {
+        return Ok(spot_price);
+    }
+    if elapsed <= 0 {
+        return Ok(ema_price);
+    }
+
+    // ema + (spot - ema) * elapsed / window
+    let delta = (spot_price as i128 - ema_price as i128)
+        .checked_mul(elapsed as i128)
+        .ok_or(SwapError::CalculationOverflow)?
+        .checked_div(PRICE_EMA_WINDOW as i128)
+        .ok_or(SwapError::MathError)?;
+
+    Ok((ema_price as i128 + delta) as u64)
+}
+
+/// Update the pool EMA price with the spot price held since the last update
+///
+/// Must be called before reserves change so the elapsed time is weighted
+/// with the price that was actually in effect.
+pub fn refresh_ema_price(pool: &mut LiquidityPool, now: i64) -> Result<()> {
+    if pool.reserve_a > 0 && pool.reserve_b > 0 {
+        let spot_price = pool_spot_price(pool)?;
+        let elapsed = now.saturating_sub(pool.ema_last_update);
+        pool.ema_price = calculate_ema_price(pool.ema_price, spot_price, elapsed)?;
+    }
+    pool.ema_last_update = now;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::price::PRICE_PRECISION;
 
     #[test]
     fn test_calculate_swap_with_fee() {
@@ -148,4 +433,106 @@ mod tests {
         assert!(impact > 0);
         assert!(impact < 100); // Less than 1%
     }
+
+    #[test]
+    fn test_calculate_ema_price() {
+        // First observation seeds the EMA
+        assert_eq!(calculate_ema_price(0, 2_000, 0).unwrap(), 2_000);
+
+        // Same-transaction spot moves are ignored
+        assert_eq!(calculate_ema_price(1_000, 5_000, 0).unwrap(), 1_000);
+
+        // Partial window moves proportionally in both directions
+        assert_eq!(calculate_ema_price(1_000, 2_000, PRICE_EMA_WINDOW / 2).unwrap(), 1_500);
+        assert_eq!(calculate_ema_price(2_000, 1_000, PRICE_EMA_WINDOW / 2).unwrap(), 1_500);
+
+        // A full window catches up to spot
+        assert_eq!(calculate_ema_price(1_000, 2_000, PRICE_EMA_WINDOW).unwrap(), 2_000);
+    }
+
+    #[test]
+    fn test_calculate_max_fill_input() {
+        let reserve_in = 100_000u64;
+        let reserve_out = 100_000u64;
+
+        // No minimum fills everything
+        assert_eq!(calculate_max_fill_input(50_000, 0, reserve_in, reserve_out, 30).unwrap(), 50_000);
+
+        // Loose minimum fills everything
+        assert_eq!(calculate_max_fill_input(1_000, 900, reserve_in, reserve_out, 30).unwrap(), 1_000);
+
+        // Tight minimum fills partially, and the partial fill respects the price
+        let fill = calculate_max_fill_input(50_000, 45_000, reserve_in, reserve_out, 30).unwrap();
+        assert!(fill > 0 && fill < 50_000);
+        let (amount_out, _) = calculate_swap_with_fee(fill, reserve_in, reserve_out, 30).unwrap();
+        assert!(amount_out as u128 * 50_000 >= 45_000u128 * fill as u128);
+
+        // Minimum above spot cannot be filled at all
+        assert_eq!(calculate_max_fill_input(1_000, 1_000, reserve_in, reserve_out, 30).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_calculate_trailing_trigger() {
+        // Trailing the high by a fixed offset
+        assert_eq!(calculate_trailing_trigger(1_000, 50, 0, TriggerCondition::AtOrBelow).unwrap(), 950);
+        // Trailing the low by 5%
+        assert_eq!(calculate_trailing_trigger(1_000, 0, 500, TriggerCondition::AtOrAbove).unwrap(), 1_050);
+        // Offset swallowing the whole price is rejected
+        assert!(calculate_trailing_trigger(1_000, 1_000, 0, TriggerCondition::AtOrBelow).is_err());
+
+        // The best price only ratchets favorably
+        assert!(is_trailing_price_better(1_100, 1_000, TriggerCondition::AtOrBelow));
+        assert!(!is_trailing_price_better(900, 1_000, TriggerCondition::AtOrBelow));
+        assert!(is_trailing_price_better(900, 1_000, TriggerCondition::AtOrAbove));
+        assert!(!is_trailing_price_better(1_000, 1_000, TriggerCondition::AtOrAbove));
+    }
+
+    #[test]
+    fn test_calculate_amm_depth() {
+        assert_eq!(integer_sqrt(4_000_000_000_000), 2_000_000);
+        assert_eq!(integer_sqrt(15), 3);
+
+        // 1:1 pool at price 1.0
+        let scale = PriceScale::new(6, 6);
+        let reserve = 1_000_000u64;
+        let spot = PRICE_PRECISION as u64;
+
+        // Selling token A down to 0.25 doubles reserve A
+        assert_eq!(calculate_amm_depth(reserve, reserve, 0, spot / 4, scale).unwrap(), 1_000_000);
+        // Buying token A up to 4.0 halves reserve A
+        assert_eq!(calculate_amm_depth(reserve, reserve, 0, spot * 4, scale).unwrap(), 500_000);
+        assert_eq!(calculate_amm_depth(reserve, reserve, 30, spot, scale).unwrap(), 0);
+
+        // The fee grows the input needed to move the price
+        assert!(calculate_amm_depth(reserve, reserve, 30, spot / 4, scale).unwrap() > 1_000_000);
+
+        // Selling the computed depth lands at (about) the target price
+        let depth = calculate_amm_depth(reserve, reserve, 30, spot / 4, scale).unwrap();
+        let (amount_out, _) = calculate_swap_with_fee(depth, reserve, reserve, 30).unwrap();
+        let price = scale.spot_price(reserve + depth, reserve - amount_out).unwrap();
+        assert!(price.abs_diff(spot / 4) <= spot / 1000);
+
+        // Mints with different decimals: 1_000 SOL against 150_000 USDC at 150
+        let sol_usdc = PriceScale::new(9, 6);
+        let depth = calculate_amm_depth(1_000_000_000_000, 150_000_000_000, 0, 600 * spot, sol_usdc).unwrap();
+        assert_eq!(depth, 500_000_000_000);
+    }
+
+    #[test]
+    fn test_validate_oco_prices() {
+        // Buy: take profit above the stop, stop-limit at or below the trigger
+        assert!(validate_oco_prices(OrderSide::Buy, 200, 100, 0).is_ok());
+        assert!(validate_oco_prices(OrderSide::Buy, 200, 100, 90).is_ok());
+        assert!(validate_oco_prices(OrderSide::Buy, 100, 200, 0).is_err());
+        assert!(validate_oco_prices(OrderSide::Buy, 200, 100, 110).is_err());
+
+        // Sell: take profit below the stop, stop-limit at or above the trigger
+        assert!(validate_oco_prices(OrderSide::Sell, 100, 200, 0).is_ok());
+        assert!(validate_oco_prices(OrderSide::Sell, 100, 200, 210).is_ok());
+        assert!(validate_oco_prices(OrderSide::Sell, 200, 100, 0).is_err());
+        assert!(validate_oco_prices(OrderSide::Sell, 100, 200, 190).is_err());
+
+        assert!(validate_oco_prices(OrderSide::Buy, 0, 100, 0).is_err());
+        assert!(validate_oco_prices(OrderSide::Buy, 200, 0, 0).is_err());
+    }
 }
@@ -20,3 +20,12 @@ pub const TOKEN_STATE_SEED: &[u8] = b"rush_token_state";
 
 /// Mint authority seed
 pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+
+/// Stake account seed
+pub const STAKE_SEED: &[u8] = b"rush_stake";
+
+/// Stake vault seed
+pub const STAKE_VAULT_SEED: &[u8] = b"rush_stake_vault";
+
+/// Seconds a stake must age before it counts as active (1 day)
+pub const STAKE_WARMUP_PERIOD: i64 = 86_400;
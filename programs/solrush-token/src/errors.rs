@@ -22,4 +22,10 @@ pub enum TokenError {
     
     #[msg("Invalid token state")]
     InvalidTokenState,
+    
+    #[msg("Invalid stake amount")]
+    InvalidStakeAmount,
+    
+    #[msg("Insufficient staked balance")]
+    InsufficientStake,
 }
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{constants::*, errors::TokenError, state::{StakeAccount, TokenState}};
+
+#[derive(Accounts)]
+pub struct InitializeStakeAccount<'info> {
+    #[account(
+        seeds = [TOKEN_STATE_SEED],
+        bump = token_state.bump,
+        constraint = token_state.is_initialized @ TokenError::InvalidTokenState,
+    )]
+    pub token_state: Account<'info, TokenState>,
+
+    #[account(
+        constraint = token_mint.key() == token_state.token_mint @ TokenError::InvalidTokenState,
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = StakeAccount::LEN,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [STAKE_VAULT_SEED, owner.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = stake_account,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<InitializeStakeAccount>) -> Result<()> {
+    let stake_account = &mut ctx.accounts.stake_account;
+
+    stake_account.bump = ctx.bumps.stake_account;
+    stake_account.owner = ctx.accounts.owner.key();
+    stake_account.amount = 0;
+    stake_account.staked_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
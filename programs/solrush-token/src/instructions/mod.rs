@@ -3,9 +3,15 @@ pub mod mint_rush_tokens;
 pub mod transfer_mint_authority;
 pub mod get_total_supply;
 pub mod get_circulating_supply;
+pub mod initialize_stake_account;
+pub mod stake_rush;
+pub mod unstake_rush;
 
 pub use initialize_rush_token::*;
 pub use mint_rush_tokens::*;
 pub use transfer_mint_authority::*;
 pub use get_total_supply::*;
 pub use get_circulating_supply::*;
+pub use initialize_stake_account::*;
+pub use stake_rush::*;
+pub use unstake_rush::*;
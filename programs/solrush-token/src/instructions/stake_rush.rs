@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::{constants::*, errors::TokenError, state::{StakeAccount, TokenState}};
+
+#[derive(Accounts)]
+pub struct StakeRush<'info> {
+    #[account(
+        mut,
+        seeds = [TOKEN_STATE_SEED],
+        bump = token_state.bump,
+    )]
+    pub token_state: Account<'info, TokenState>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == token_state.token_mint @ TokenError::InvalidTokenState,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<StakeRush>, amount: u64) -> Result<()> {
+    require!(amount > 0, TokenError::InvalidStakeAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let clock = Clock::get()?;
+    let stake_account = &mut ctx.accounts.stake_account;
+    stake_account.amount = stake_account.amount
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    stake_account.staked_at = clock.unix_timestamp;
+
+    let token_state = &mut ctx.accounts.token_state;
+    token_state.locked_tokens = token_state.locked_tokens
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    token_state.circulating_supply = token_state.circulating_supply.saturating_sub(amount);
+
+    emit!(RushStaked {
+        owner: stake_account.owner,
+        amount,
+        total_staked: stake_account.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RushStaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::{constants::*, errors::TokenError, state::{StakeAccount, TokenState}};
+
+#[derive(Accounts)]
+pub struct UnstakeRush<'info> {
+    #[account(
+        mut,
+        seeds = [TOKEN_STATE_SEED],
+        bump = token_state.bump,
+    )]
+    pub token_state: Account<'info, TokenState>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED, owner.key().as_ref()],
+        bump,
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == token_state.token_mint @ TokenError::InvalidTokenState,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<UnstakeRush>, amount: u64) -> Result<()> {
+    require!(amount > 0, TokenError::InvalidStakeAmount);
+
+    let stake_account = &mut ctx.accounts.stake_account;
+    require!(amount <= stake_account.amount, TokenError::InsufficientStake);
+
+    let owner_key = stake_account.owner;
+    let seeds = &[
+        STAKE_SEED,
+        owner_key.as_ref(),
+        &[stake_account.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: stake_account.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    stake_account.amount -= amount;
+
+    let token_state = &mut ctx.accounts.token_state;
+    token_state.locked_tokens = token_state.locked_tokens.saturating_sub(amount);
+    token_state.circulating_supply = token_state.circulating_supply
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+
+    emit!(RushUnstaked {
+        owner: owner_key,
+        amount,
+        total_staked: stake_account.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RushUnstaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
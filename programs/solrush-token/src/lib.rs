@@ -57,4 +57,27 @@ pub mod solrush_token {
     pub fn get_circulating_supply(ctx: Context<GetCirculatingSupply>) -> Result<u64> {
         instructions::get_circulating_supply::handler(ctx)
     }
+
+    /// Create a user's RUSH stake account and vault
+    pub fn initialize_stake_account(ctx: Context<InitializeStakeAccount>) -> Result<()> {
+        instructions::initialize_stake_account::handler(ctx)
+    }
+
+    /// Stake RUSH (counts toward swap fee discounts after a 1 day warmup)
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `amount` - Amount to stake
+    pub fn stake_rush(ctx: Context<StakeRush>, amount: u64) -> Result<()> {
+        instructions::stake_rush::handler(ctx, amount)
+    }
+
+    /// Unstake RUSH back to the owner
+    /// 
+    /// # Arguments
+    /// * `ctx` - Context with required accounts
+    /// * `amount` - Amount to unstake
+    pub fn unstake_rush(ctx: Context<UnstakeRush>, amount: u64) -> Result<()> {
+        instructions::unstake_rush::handler(ctx, amount)
+    }
 }
@@ -44,6 +44,40 @@ impl TokenState {
         8; // created_at
 }
 
+/// Per-user RUSH stake
+#[account]
+pub struct StakeAccount {
+    /// Bump seed
+    pub bump: u8,
+    
+    /// Staker
+    pub owner: Pubkey,
+    
+    /// Staked amount held in the stake vault
+    pub amount: u64,
+    
+    /// Time of the last stake; restarts the warmup
+    pub staked_at: i64,
+}
+
+impl StakeAccount {
+    pub const LEN: usize = 8 + // discriminator
+        1 + // bump
+        32 + // owner
+        8 + // amount
+        8; // staked_at
+
+    /// Stake that has finished warming up, so it cannot be borrowed just for
+    /// one transaction
+    pub fn active_amount(&self, now: i64) -> u64 {
+        if now.saturating_sub(self.staked_at) >= crate::constants::STAKE_WARMUP_PERIOD {
+            self.amount
+        } else {
+            0
+        }
+    }
+}
+
 /// Supply information
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct SupplyInfo {